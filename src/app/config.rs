@@ -4,7 +4,33 @@ pub const APP_ID: &str = match cfg!(debug_assertions) {
 };
 pub const APP_NAME: &str = "Stremio";
 
+pub const AUTOSTART_COMMAND: &[&str] = &["stremio", "--hidden"];
+
+pub const AUTO_AUDIO_DEVICE: &str = "auto";
+pub const DEFAULT_AUDIO_CHANNELS: &str = "auto-safe";
+pub const DEFAULT_SKIP_PATTERNS: &[&str] = &[
+    "intro",
+    "opening",
+    "op",
+    "recap",
+    "previously on",
+    "credits",
+    "ending",
+    "ed",
+    "outro",
+];
+
 pub const URI_SCHEME: &str = "stremio://";
 pub const URL_PROD: &str = "http://127.0.0.1:11470/proxy/d=https%3A%2F%2Fweb.stremio.com/";
 pub const URL_DEV: &str = "http://localhost:8080/";
+pub const WEB_NOTIFICATION_ID: &str = "web";
+/// Application actions notifications can activate, others are forwarded to the web UI
+pub const NOTIFICATION_ACTIONS: &[&str] = &["show", "quit"];
+pub const SERVER_NOTIFICATION_ID: &str = "server";
+pub const SHADER_NOTIFICATION_ID: &str = "shader";
+pub const SERVER_POLL_INTERVAL: u32 = 10;
+pub const RESUME_OFFER_TIMEOUT: u32 = 15;
+/// Web process crashes within the interval, in seconds, before giving up on reloading
+pub const WEB_CRASH_LIMIT: usize = 3;
+pub const WEB_CRASH_INTERVAL: u64 = 60;
 pub const PRELOAD_SCRIPT: &str = include_str!("preload.js");
@@ -0,0 +1,294 @@
+use std::{
+    cell::{Cell, RefCell},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use ashpd::{WindowIdentifier, desktop::file_chooser::SelectedFiles};
+use gettextrs::gettext;
+use gtk::{
+    glib::{self, clone, object::ObjectExt, subclass::Signal, types::StaticType},
+    subclass::prelude::*,
+};
+use serde::Serialize;
+use tracing::error;
+use webkit::{Download, DownloadError, prelude::*};
+
+use crate::spawn_local;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadState {
+    Active,
+    Finished,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadInfo {
+    pub id: u32,
+    pub uri: String,
+    pub filename: String,
+    pub destination: Option<PathBuf>,
+    pub state: DownloadState,
+    pub progress: f64,
+    pub received: u64,
+    pub total: u64,
+}
+
+struct DownloadEntry {
+    info: DownloadInfo,
+    download: Download,
+}
+
+#[derive(Default)]
+pub struct Downloads {
+    entries: RefCell<Vec<DownloadEntry>>,
+    next_id: Cell<u32>,
+}
+
+impl Downloads {
+    pub fn track(&self, download: &Download, ask: bool, parent: Option<gtk::Window>) {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+
+        let uri = download
+            .request()
+            .and_then(|request| request.uri())
+            .map(String::from)
+            .unwrap_or_default();
+
+        self.entries.borrow_mut().push(DownloadEntry {
+            info: DownloadInfo {
+                id,
+                uri,
+                filename: String::new(),
+                destination: None,
+                state: DownloadState::Active,
+                progress: 0.0,
+                received: 0,
+                total: 0,
+            },
+            download: download.clone(),
+        });
+
+        download.connect_decide_destination(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            #[upgrade_or]
+            false,
+            move |download, suggested_filename| {
+                imp.update(id, |info| info.filename = suggested_filename.to_owned());
+
+                match ask {
+                    true => imp.ask_destination(download, suggested_filename, parent.clone()),
+                    false => match dirs::download_dir() {
+                        Some(dir) => {
+                            let path = unique_path(&dir, suggested_filename);
+                            download.set_destination(&path.to_string_lossy());
+                        }
+                        None => {
+                            error!("Failed to get download dir");
+                            download.cancel();
+                        }
+                    },
+                }
+
+                true
+            }
+        ));
+
+        download.connect_created_destination(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |download, destination| {
+                imp.update(id, |info| {
+                    info.destination = Some(PathBuf::from(destination));
+                    info.total = download
+                        .response()
+                        .map(|response| response.content_length())
+                        .unwrap_or_default();
+                });
+            }
+        ));
+
+        download.connect_received_data(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |download, _| {
+                let progress = download.estimated_progress();
+                let received = download.received_data_length();
+
+                let previous = imp.info(id).map(|info| info.progress).unwrap_or_default();
+                if (progress * 100.0).floor() > (previous * 100.0).floor() {
+                    imp.update(id, |info| {
+                        info.progress = progress;
+                        info.received = received;
+                    });
+                }
+            }
+        ));
+
+        download.connect_failed(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |_, error| {
+                let state = match error.matches(DownloadError::CancelledByUser) {
+                    true => DownloadState::Cancelled,
+                    false => {
+                        error!("Failed to download: {error}");
+                        DownloadState::Failed
+                    }
+                };
+
+                imp.update(id, |info| info.state = state);
+            }
+        ));
+
+        download.connect_finished(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |download| {
+                let finished = imp
+                    .info(id)
+                    .is_some_and(|info| info.state == DownloadState::Active);
+
+                if finished {
+                    imp.update(id, |info| {
+                        info.state = DownloadState::Finished;
+                        info.progress = 1.0;
+                        info.received = download.received_data_length();
+                    });
+
+                    imp.obj().emit_by_name::<()>("finished", &[&id]);
+                }
+            }
+        ));
+
+        self.obj().emit_by_name::<()>("changed", &[&id]);
+    }
+
+    pub fn info(&self, id: u32) -> Option<DownloadInfo> {
+        self.entries
+            .borrow()
+            .iter()
+            .find(|entry| entry.info.id == id)
+            .map(|entry| entry.info.clone())
+    }
+
+    pub fn list(&self) -> Vec<DownloadInfo> {
+        self.entries
+            .borrow()
+            .iter()
+            .map(|entry| entry.info.clone())
+            .collect()
+    }
+
+    pub fn cancel(&self, id: u32) {
+        let download = self
+            .entries
+            .borrow()
+            .iter()
+            .find(|entry| entry.info.id == id && entry.info.state == DownloadState::Active)
+            .map(|entry| entry.download.clone());
+
+        if let Some(download) = download {
+            download.cancel();
+        }
+    }
+
+    fn update<F: FnOnce(&mut DownloadInfo)>(&self, id: u32, f: F) {
+        if let Some(entry) = self
+            .entries
+            .borrow_mut()
+            .iter_mut()
+            .find(|entry| entry.info.id == id)
+        {
+            f(&mut entry.info);
+        }
+
+        self.obj().emit_by_name::<()>("changed", &[&id]);
+    }
+
+    fn ask_destination(&self, download: &Download, name: &str, parent: Option<gtk::Window>) {
+        let download = download.clone();
+        let name = name.to_owned();
+
+        spawn_local!(async move {
+            let identifier = match parent {
+                Some(ref parent) => WindowIdentifier::from_native(parent).await,
+                None => None,
+            };
+
+            let title = gettext("Save File");
+            let request = SelectedFiles::save_file()
+                .identifier(identifier)
+                .title(title.as_str())
+                .current_name(name.as_str())
+                .modal(true);
+
+            let path = match request.send().await.and_then(|request| request.response()) {
+                Ok(files) => files.uris().first().and_then(|uri| uri.to_file_path().ok()),
+                Err(e) => {
+                    error!("Failed to choose download destination: {e}");
+                    None
+                }
+            };
+
+            match path {
+                Some(path) => {
+                    download.set_allow_overwrite(true);
+                    download.set_destination(&path.to_string_lossy());
+                }
+                None => download.cancel(),
+            }
+        });
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for Downloads {
+    const NAME: &'static str = "Downloads";
+    type Type = super::Downloads;
+    type ParentType = glib::Object;
+}
+
+impl ObjectImpl for Downloads {
+    fn signals() -> &'static [Signal] {
+        static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+        SIGNALS.get_or_init(|| {
+            vec![
+                Signal::builder("changed")
+                    .param_types([u32::static_type()])
+                    .build(),
+                Signal::builder("finished")
+                    .param_types([u32::static_type()])
+                    .build(),
+            ]
+        })
+    }
+}
+
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let path = dir.join(name);
+    if !path.exists() {
+        return path;
+    }
+
+    let name = Path::new(name);
+    let stem = name
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = name
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1..)
+        .map(|index| dir.join(format!("{stem} ({index}){extension}")))
+        .find(|path| !path.exists())
+        .unwrap_or(path)
+}
@@ -0,0 +1,54 @@
+mod imp;
+
+use adw::subclass::prelude::ObjectSubclassIsExt;
+use gtk::glib::{self, closure_local, object::ObjectExt};
+
+pub use imp::DownloadInfo;
+
+glib::wrapper! {
+    pub struct Downloads(ObjectSubclass<imp::Downloads>);
+}
+
+impl Default for Downloads {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
+
+impl Downloads {
+    pub fn track(&self, download: &webkit::Download, ask: bool, parent: Option<gtk::Window>) {
+        self.imp().track(download, ask, parent);
+    }
+
+    pub fn list(&self) -> Vec<DownloadInfo> {
+        self.imp().list()
+    }
+
+    pub fn cancel(&self, id: u32) {
+        self.imp().cancel(id);
+    }
+
+    pub fn connect_changed<T: Fn(DownloadInfo) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "changed",
+            false,
+            closure_local!(move |downloads: Downloads, id: u32| {
+                if let Some(info) = downloads.imp().info(id) {
+                    callback(info);
+                }
+            }),
+        );
+    }
+
+    pub fn connect_finished<T: Fn(DownloadInfo) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "finished",
+            false,
+            closure_local!(move |downloads: Downloads, id: u32| {
+                if let Some(info) = downloads.imp().info(id) {
+                    callback(info);
+                }
+            }),
+        );
+    }
+}
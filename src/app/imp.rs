@@ -1,32 +1,85 @@
 use std::{
     cell::{Cell, RefCell},
     rc::Rc,
+    time::{Duration, Instant},
 };
 
 use adw::{prelude::*, subclass::prelude::*};
-use gtk::glib::{self, Properties, clone};
+use chrono::{DateTime, Utc};
+use gettextrs::gettext;
+use gtk::{
+    gio,
+    glib::{self, Properties, clone},
+};
+use itertools::Itertools;
+use tracing::{debug, error};
 
-use crate::app::{
-    config::{PRELOAD_SCRIPT, URI_SCHEME, URL_DEV, URL_PROD},
-    ipc::{
-        self,
-        event::{IpcEvent, IpcEventMpv},
+use crate::{
+    app::{
+        config::{
+            APP_NAME, AUTO_AUDIO_DEVICE, DEFAULT_AUDIO_CHANNELS, NOTIFICATION_ACTIONS,
+            PRELOAD_SCRIPT, RESUME_OFFER_TIMEOUT, SERVER_POLL_INTERVAL, SHADER_NOTIFICATION_ID,
+            URI_SCHEME, URL_DEV, URL_PROD, WEB_CRASH_INTERVAL, WEB_CRASH_LIMIT,
+            WEB_NOTIFICATION_ID,
+        },
+        downloads::Downloads,
+        ipc::{
+            self,
+            event::{IpcEvent, IpcEventMpv},
+        },
+        notifier::Notifier,
+        pip::PipWindow,
+        presentation::{self, PresentationWindow},
+        resume::{self, ResumeStore},
+        settings::{HiddenPlayback, Settings, ShowOverride},
+        shaders::{self, ShaderPreset},
+        stats::Stats,
+        tray::{Tray, TrayPlayback},
+        video::{self, PlaybackState, Video},
+        webview::WebView,
+        window::Window,
     },
-    tray::Tray,
-    video::Video,
-    webview::WebView,
-    window::Window,
+    server, spawn_local,
 };
 
+struct WebCrash {
+    time: Instant,
+    date: DateTime<Utc>,
+    reason: String,
+}
+
 #[derive(Properties, Default)]
 #[properties(wrapper_type = super::Application)]
 pub struct Application {
     #[property(get, set)]
     dev_mode: Cell<bool>,
-    tray: RefCell<Option<Tray>>,
-    video: RefCell<Option<Video>>,
-    webview: RefCell<Option<WebView>>,
+    #[property(get, set)]
+    hidden: Cell<bool>,
+    #[property(get, set)]
+    software_rendering: Cell<bool>,
+    pub settings: RefCell<Settings>,
+    pub tray: RefCell<Option<Tray>>,
+    pub video: RefCell<Option<Video>>,
+    pub webview: RefCell<Option<WebView>>,
+    pub notifier: RefCell<Option<Notifier>>,
+    pub downloads: RefCell<Option<Downloads>>,
+    pub resume_offer: Cell<Option<f64>>,
+    resume_offer_button: RefCell<Option<gtk::Button>>,
+    resume_offer_timeout: RefCell<Option<glib::SourceId>>,
+    resume: RefCell<ResumeStore>,
+    /// Whether the current media is resumed by the shell rather than the web UI
+    resume_media: Cell<bool>,
+    deeplink_opened: Cell<bool>,
+    show_override_applied: Cell<bool>,
+    paused_on_hide: Cell<bool>,
+    window_fitted: Cell<bool>,
+    pip: RefCell<Option<PipWindow>>,
+    presentation: RefCell<Option<PresentationWindow>>,
+    shader_presets: RefCell<Vec<ShaderPreset>>,
+    web_crashes: RefCell<Vec<WebCrash>>,
     deeplink: Rc<RefCell<Option<String>>>,
+    server_active: Cell<bool>,
+    server_poll: RefCell<Option<glib::SourceId>>,
 }
 
 #[glib::object_subclass]
@@ -43,9 +96,34 @@ impl ApplicationImpl for Application {
     fn startup(&self) {
         self.parent_startup();
 
+        *self.settings.borrow_mut() = Settings::load();
+        *self.resume.borrow_mut() = ResumeStore::load();
+        *self.shader_presets.borrow_mut() = shaders::load_presets();
+
         let app = self.obj();
         app.setup_actions();
+        app.setup_remote_actions();
+        app.setup_video_actions();
         app.setup_accels();
+
+        let notifier = Notifier::default();
+        notifier.connect_action(clone!(
+            #[weak]
+            app,
+            move |id, name, target| {
+                if NOTIFICATION_ACTIONS.contains(&name) {
+                    app.activate_action(name, target.map(|target| target.to_variant()).as_ref());
+                } else if let Some(ref webview) = *app.imp().webview.borrow() {
+                    let message = ipc::create_response(IpcEvent::NotificationAction((
+                        id.to_owned(),
+                        name.to_owned(),
+                    )));
+                    webview.send(&message);
+                }
+            }
+        ));
+
+        *self.notifier.borrow_mut() = Some(notifier);
     }
 
     fn activate(&self) {
@@ -59,6 +137,7 @@ impl ApplicationImpl for Application {
         }
 
         let tray = Tray::default();
+        let downloads = Downloads::default();
         let video = Video::default();
 
         let dev_mode = self.dev_mode.get();
@@ -74,22 +153,288 @@ impl ApplicationImpl for Application {
         webview.dev_mode(dev_mode);
 
         let window = Window::new(&app);
-        window.set_underlay(&video);
-        window.set_overlay(&webview);
+        if self.software_rendering.get() || !video::gl_available() {
+            video.use_software_rendering();
+        }
 
-        video.connect_playback_started(clone!(
+        match video.picture() {
+            Some(picture) => window.set_underlay(&picture),
+            None => window.set_underlay(&video),
+        }
+
+        video.connect_renderer_notify(clone!(
             #[weak]
             window,
+            #[weak]
+            webview,
+            move |video| {
+                if let Some(picture) = video.picture() {
+                    // Deferred since the video area is still being realized
+                    glib::idle_add_local_once(clone!(
+                        #[weak]
+                        window,
+                        move || {
+                            window.set_underlay(&picture);
+                        }
+                    ));
+                }
+
+                let message =
+                    ipc::create_response(IpcEvent::Renderer(video.renderer().as_str().to_owned()));
+                webview.send(&message);
+            }
+        ));
+        window.set_overlay(&webview);
+
+        let stats = Stats::new(&video);
+        window.add_overlay(&stats);
+        window.add_action(&gio::PropertyAction::new("toggle-stats", &stats, "visible"));
+
+        let skip_button = gtk::Button::builder()
+            .action_name("app.skip-chapter")
+            .css_classes(["pill", "suggested-action"])
+            .halign(gtk::Align::End)
+            .valign(gtk::Align::End)
+            .margin_end(24)
+            .margin_bottom(96)
+            .visible(false)
+            .build();
+        window.add_overlay(&skip_button);
+
+        let resume_button = gtk::Button::builder()
+            .action_name("app.resume")
+            .css_classes(["pill", "suggested-action"])
+            .halign(gtk::Align::Start)
+            .valign(gtk::Align::End)
+            .margin_start(24)
+            .margin_bottom(96)
+            .visible(false)
+            .build();
+        window.add_overlay(&resume_button);
+        *self.resume_offer_button.borrow_mut() = Some(resume_button);
+
+        video.connect_path_notify(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |video| {
+                let Some(path) = video.path() else {
+                    imp.resume_media.set(false);
+                    imp.withdraw_resume_offer();
+                    return;
+                };
+
+                let resumable = resume::is_resumable(&path, imp.deeplink_opened.take());
+                imp.resume_media.set(resumable);
+
+                let position = resumable.then(|| imp.resume.borrow().get(&path)).flatten();
+
+                match position {
+                    Some(position) => imp.offer_resume(position),
+                    None => imp.withdraw_resume_offer(),
+                }
+            }
+        ));
+
+        video.connect_media_title_notify(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |_| {
+                // Deferred since this is emitted while mpv is handling an event
+                glib::idle_add_local_once(clone!(
+                    #[weak]
+                    imp,
+                    move || {
+                        imp.apply_show_override();
+                    }
+                ));
+            }
+        ));
+
+        video.connect_playback_started(clone!(
+            #[weak(rename_to = imp)]
+            self,
             move || {
-                window.disable_idling();
+                imp.window_fitted.set(false);
+            }
+        ));
+
+        video.connect_video_resized(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |video| {
+                imp.apply_aspect_lock();
+
+                // Only fit once per file so later manual resizes are kept
+                if video.video_size().is_some()
+                    && imp.settings.borrow().fit_window
+                    && !imp.window_fitted.replace(true)
+                {
+                    imp.fit_window(1.0);
+                }
+            }
+        ));
+
+        video.connect_paused_notify(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |video| {
+                match video.paused() {
+                    true => imp.save_resume_position(),
+                    // Played again through MPRIS or the tray while hidden,
+                    // so the user decides whether it plays on showing again
+                    false => imp.paused_on_hide.set(false),
+                }
             }
         ));
 
         video.connect_playback_ended(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move || {
+                imp.save_resume_position();
+            }
+        ));
+
+        window.set_shader_presets(&self.shader_presets.borrow());
+        window.set_monitors(&presentation::monitors());
+
+        if let Some(display) = gtk::gdk::Display::default() {
+            display.monitors().connect_items_changed(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                #[weak]
+                window,
+                move |_, _, _, _| {
+                    window.set_monitors(&presentation::monitors());
+                    imp.send_monitors();
+
+                    let lost = imp
+                        .presentation
+                        .borrow()
+                        .as_ref()
+                        .is_some_and(|presentation| presentation.monitor_lost());
+
+                    if lost {
+                        imp.present_on("");
+                    }
+                }
+            ));
+        }
+
+        video.connect_shader_error(clone!(
+            #[weak]
+            app,
+            #[weak]
+            webview,
+            move |message| {
+                error!("Failed to load shaders: {message}");
+
+                if let Some(ref notifier) = *app.imp().notifier.borrow() {
+                    notifier.send(
+                        SHADER_NOTIFICATION_ID,
+                        &gettext("Shaders failed to load and were disabled"),
+                        Some(message),
+                        &[],
+                    );
+                }
+
+                let message = ipc::create_response(IpcEvent::ShaderError(message.to_owned()));
+                webview.send(&message);
+
+                // Deferred since this is emitted while mpv is handling an event
+                glib::idle_add_local_once(clone!(
+                    #[weak]
+                    app,
+                    move || {
+                        app.activate_action("shader-preset", Some(&"".to_variant()));
+                    }
+                ));
+            }
+        ));
+
+        video.connect_skippable_chapter_notify(clone!(
+            #[weak]
+            app,
+            #[weak]
+            webview,
+            #[weak]
+            skip_button,
+            move |video| {
+                let title = video.skippable_chapter();
+
+                if let Some(ref title) = title {
+                    skip_button.set_label(&format!("{} {title}", gettext("Skip")));
+                }
+                skip_button.set_visible(title.is_some());
+
+                if let Some(action) = app
+                    .lookup_action("skip-chapter")
+                    .and_downcast::<gio::SimpleAction>()
+                {
+                    action.set_enabled(title.is_some());
+                }
+
+                let message = ipc::create_response(IpcEvent::ChapterSkippable(title.clone()));
+                webview.send(&message);
+
+                // Deferred since this is emitted while mpv is handling an event
+                if title.is_some() && app.imp().settings.borrow().auto_skip {
+                    glib::idle_add_local_once(clone!(
+                        #[weak]
+                        video,
+                        move || {
+                            video.skip_chapter();
+                        }
+                    ));
+                }
+            }
+        ));
+
+        video.connect_playback_state_notify(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |_| {
+                imp.update_inhibit();
+            }
+        ));
+
+        video.connect_active_notify(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |_| {
+                imp.update_server_poll();
+            }
+        ));
+
+        video.connect_notify_local(
+            None,
+            clone!(
+                #[weak]
+                tray,
+                move |video, pspec| {
+                    if ["active", "paused", "muted", "volume", "title"].contains(&pspec.name()) {
+                        tray.update_playback(tray_playback(video));
+                    }
+                }
+            ),
+        );
+
+        video.connect_audio_devices_changed(clone!(
+            #[weak(rename_to = imp)]
+            self,
             #[weak]
             window,
-            move || {
-                window.enable_idling();
+            move |video| {
+                window.set_audio_devices(&video.audio_devices());
+
+                // Deferred since this is emitted while mpv is handling an event
+                glib::idle_add_local_once(clone!(
+                    #[weak]
+                    imp,
+                    move || {
+                        imp.apply_audio_device();
+                    }
+                ));
             }
         ));
 
@@ -122,6 +467,11 @@ impl ApplicationImpl for Application {
                             webview.send(&message);
                         }
                         IpcEvent::Ready => {
+                            let message = ipc::create_response(IpcEvent::Renderer(
+                                video.renderer().as_str().to_owned(),
+                            ));
+                            webview.send(&message);
+
                             if let Some(ref uri) = *deeplink.borrow() {
                                 let message =
                                     ipc::create_response(IpcEvent::OpenMedia(uri.to_string()));
@@ -137,9 +487,96 @@ impl ApplicationImpl for Application {
                         IpcEvent::Quit => {
                             app.quit();
                         }
+                        IpcEvent::Downloads(_) => {
+                            if let Some(ref downloads) = *app.imp().downloads.borrow()
+                                && let Ok(list) = serde_json::to_value(downloads.list())
+                            {
+                                let message = ipc::create_response(IpcEvent::Downloads(Some(list)));
+                                webview.send(&message);
+                            }
+                        }
+                        IpcEvent::DownloadCancel(id) => {
+                            if let Some(ref downloads) = *app.imp().downloads.borrow() {
+                                downloads.cancel(id);
+                            }
+                        }
+                        IpcEvent::Notify(notification) => {
+                            if let Some(ref notifier) = *app.imp().notifier.borrow() {
+                                let buttons = notification
+                                    .actions
+                                    .iter()
+                                    .map(|action| (action.label.as_str(), action.action.as_str()))
+                                    .collect_vec();
+
+                                notifier.send(
+                                    notification.id.as_deref().unwrap_or(WEB_NOTIFICATION_ID),
+                                    &notification.title,
+                                    notification.body.as_deref(),
+                                    &buttons,
+                                );
+                            }
+                        }
+                        IpcEvent::ShaderPresets(_) => {
+                            let presets = app
+                                .imp()
+                                .shader_presets
+                                .borrow()
+                                .iter()
+                                .map(|preset| preset.name.clone())
+                                .collect_vec();
+                            let active = app.imp().settings.borrow().shader_preset.clone();
+
+                            let message = ipc::create_response(IpcEvent::ShaderPresets(Some(
+                                serde_json::json!({
+                                    "presets": presets,
+                                    "active": active,
+                                }),
+                            )));
+                            webview.send(&message);
+                        }
+                        IpcEvent::ShaderPreset(name) => {
+                            app.activate_action("shader-preset", Some(&name.to_variant()));
+                        }
+                        IpcEvent::Resume => {
+                            app.activate_action("resume", None);
+                        }
+                        IpcEvent::TogglePip => {
+                            app.activate_action("toggle-pip", None);
+                        }
+                        IpcEvent::Monitors(_) => {
+                            app.imp().send_monitors();
+                        }
+                        IpcEvent::PresentOn(id) => {
+                            app.activate_action("present-on", Some(&id.to_variant()));
+                        }
+                        IpcEvent::SkipChapter => {
+                            video.skip_chapter();
+                        }
+                        IpcEvent::SkipPatterns(patterns) => {
+                            app.update_settings(|settings| {
+                                settings.skip_patterns = Some(patterns.clone());
+                            });
+                            video.set_skip_patterns(patterns);
+                        }
                         IpcEvent::Mpv(event) => match event {
                             IpcEventMpv::Observe(name) => video.observe_property(name),
                             IpcEventMpv::Command((name, args)) => video.send_command(name, args),
+                            IpcEventMpv::Set((name, value)) if name == "audio-device" => {
+                                if let Some(device) = value.as_str() {
+                                    app.activate_action("audio-device", Some(&device.to_variant()));
+                                }
+                            }
+                            IpcEventMpv::Set((name, value)) if name == "video-crop" => {
+                                if let Some(crop) = value.as_str() {
+                                    video.set_video_crop(crop);
+                                }
+                            }
+                            IpcEventMpv::Set((name, value))
+                                if name == "video-zoom" || name == "video-aspect-override" =>
+                            {
+                                video.set_property(name, value);
+                                app.imp().save_show_override();
+                            }
                             IpcEventMpv::Set((name, value)) => video.set_property(name, value),
                             _ => {}
                         },
@@ -157,6 +594,53 @@ impl ApplicationImpl for Application {
             }
         ));
 
+        webview.connect_download_started(clone!(
+            #[weak]
+            app,
+            #[weak]
+            window,
+            #[weak]
+            downloads,
+            move |download| {
+                let ask = app.imp().settings.borrow().ask_download_location;
+                downloads.track(download, ask, Some(window.upcast()));
+            }
+        ));
+
+        downloads.connect_changed(clone!(
+            #[weak]
+            webview,
+            move |download| {
+                if let Ok(download) = serde_json::to_value(download) {
+                    let message = ipc::create_response(IpcEvent::DownloadChanged(download));
+                    webview.send(&message);
+                }
+            }
+        ));
+
+        downloads.connect_finished(clone!(
+            #[weak]
+            app,
+            move |download| {
+                if let Some(ref notifier) = *app.imp().notifier.borrow() {
+                    notifier.send(
+                        &format!("download-{}", download.id),
+                        &gettext("Download finished"),
+                        Some(download.filename.as_str()),
+                        &[],
+                    );
+                }
+            }
+        ));
+
+        webview.connect_crashed(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |reason| {
+                imp.recover_webview(reason);
+            }
+        ));
+
         webview.connect_open_external(clone!(
             #[weak]
             window,
@@ -178,6 +662,40 @@ impl ApplicationImpl for Application {
             }
         ));
 
+        window.connect_hidden(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |hidden| {
+                // Showing the main window again brings the video back from the mini player
+                if !hidden && imp.pip.borrow().is_some() {
+                    imp.toggle_pip();
+                }
+
+                imp.apply_hidden_playback(hidden);
+            }
+        ));
+
+        tray.connect_available_notify(clone!(
+            #[weak]
+            window,
+            move |tray| {
+                if !tray.available() && !window.is_visible() {
+                    window.present();
+                }
+            }
+        ));
+
+        // A window started hidden is shown when there is no tray to bring it back
+        tray.connect_ready_notify(clone!(
+            #[weak]
+            window,
+            move |tray| {
+                if !tray.available() && !window.is_visible() {
+                    window.present();
+                }
+            }
+        ));
+
         tray.connect_show(clone!(
             #[weak]
             window,
@@ -194,6 +712,57 @@ impl ApplicationImpl for Application {
             }
         ));
 
+        tray.connect_toggle(clone!(
+            #[weak]
+            window,
+            move || {
+                match window.is_visible() {
+                    true => window.set_visible(false),
+                    false => window.present(),
+                }
+            }
+        ));
+
+        tray.connect_play_pause(clone!(
+            #[weak]
+            video,
+            move || {
+                video.play_pause();
+            }
+        ));
+
+        tray.connect_stop(clone!(
+            #[weak]
+            video,
+            move || {
+                video.stop();
+            }
+        ));
+
+        tray.connect_seek(clone!(
+            #[weak]
+            video,
+            move |offset| {
+                video.seek(offset);
+            }
+        ));
+
+        tray.connect_toggle_mute(clone!(
+            #[weak]
+            video,
+            move || {
+                video.toggle_mute();
+            }
+        ));
+
+        tray.connect_volume(clone!(
+            #[weak]
+            video,
+            move |volume| {
+                video.set_volume(volume);
+            }
+        ));
+
         tray.connect_quit(clone!(
             #[weak]
             app,
@@ -203,10 +772,22 @@ impl ApplicationImpl for Application {
         ));
 
         *self.tray.borrow_mut() = Some(tray);
+        *self.downloads.borrow_mut() = Some(downloads);
         *self.video.borrow_mut() = Some(video);
         *self.webview.borrow_mut() = Some(webview);
 
-        window.present();
+        self.apply_audio_settings();
+        self.apply_video_settings();
+
+        if !self.hidden.get() {
+            window.present();
+        }
+    }
+
+    fn shutdown(&self) {
+        self.save_resume_position();
+
+        self.parent_shutdown();
     }
 
     fn open(&self, files: &[gtk::gio::File], hint: &str) {
@@ -217,16 +798,525 @@ impl ApplicationImpl for Application {
         if let Some(file) = files.first() {
             let uri = file.uri().to_string();
             if uri.starts_with(URI_SCHEME) {
-                let mut deeplink = self.deeplink.borrow_mut();
-                *deeplink = Some(uri.clone());
+                self.open_media(uri);
+            }
+        }
+    }
+}
 
-                if let Some(ref webview) = *self.webview.borrow() {
-                    let message = ipc::create_response(IpcEvent::OpenMedia(uri));
-                    webview.send(&message);
+impl Application {
+    /// Falls back to automatic selection while the preferred device is unplugged
+    pub fn apply_audio_device(&self) {
+        if let Some(ref video) = *self.video.borrow() {
+            let preferred = self.settings.borrow().audio_device.clone();
+            let device = preferred
+                .filter(|name| {
+                    video
+                        .audio_devices()
+                        .iter()
+                        .any(|device| &device.name == name)
+                })
+                .unwrap_or(AUTO_AUDIO_DEVICE.to_owned());
+
+            video.set_audio_device(&device);
+        }
+    }
+
+    pub fn apply_audio_settings(&self) {
+        if let Some(ref video) = *self.video.borrow() {
+            let settings = self.settings.borrow();
+
+            video.set_audio_passthrough(&settings.audio_spdif);
+            video.set_audio_channels(
+                settings
+                    .audio_channels
+                    .as_deref()
+                    .unwrap_or(DEFAULT_AUDIO_CHANNELS),
+            );
+            video.set_audio_exclusive(settings.audio_exclusive);
+            video.set_night_mode(settings.night_mode);
+        }
+    }
+
+    fn apply_video_settings(&self) {
+        if let Some(ref video) = *self.video.borrow() {
+            let settings = self.settings.borrow();
+
+            video.set_skip_patterns(settings.skip_patterns());
+            video.set_auto_crop(settings.auto_crop);
+        }
+
+        self.apply_shader_preset();
+    }
+
+    pub fn apply_shader_preset(&self) {
+        if let Some(ref video) = *self.video.borrow() {
+            let name = self.settings.borrow().shader_preset.clone();
+            let shaders = self
+                .shader_presets
+                .borrow()
+                .iter()
+                .find(|preset| Some(&preset.name) == name.as_ref())
+                .map(|preset| preset.shaders.clone())
+                .unwrap_or_default();
+
+            video.set_shaders(&shaders);
+        }
+    }
+
+    fn apply_hidden_playback(&self, hidden: bool) {
+        // The video is shown in a window of its own
+        if self.pip.borrow().is_some() || self.presentation.borrow().is_some() {
+            return;
+        }
+
+        if let Some(ref video) = *self.video.borrow() {
+            if !hidden {
+                video.resume_video();
+
+                if self.paused_on_hide.take() {
+                    video.set_paused(false);
+                }
+
+                return;
+            }
+
+            match self.settings.borrow().hidden_playback {
+                HiddenPlayback::Pause => {
+                    if video.active() && !video.paused() {
+                        video.set_paused(true);
+                        self.paused_on_hide.set(true);
+                    }
+                }
+                HiddenPlayback::AudioOnly => video.suspend_video(),
+                HiddenPlayback::KeepPlaying => {}
+            }
+        }
+    }
+
+    pub fn fit_window(&self, scale: f64) {
+        if let (Some(window), Some(video)) = (self.obj().window(), self.video.borrow().as_ref())
+            && let Some((width, height)) = video.video_size()
+        {
+            window.fit_video(width, height, scale);
+        }
+    }
+
+    pub fn apply_aspect_lock(&self) {
+        if let (Some(window), Some(video)) = (self.obj().window(), self.video.borrow().as_ref()) {
+            let ratio = video
+                .video_size()
+                .filter(|_| self.settings.borrow().lock_aspect)
+                .map(|(width, height)| f64::from(width) / f64::from(height));
+
+            window.set_aspect_ratio(ratio);
+        }
+    }
+
+    /// Moves the video between the main window and the mini player
+    pub fn toggle_pip(&self) {
+        let app = self.obj();
+
+        let (Some(window), Some(video)) = (app.window(), self.video.borrow().clone()) else {
+            return;
+        };
+
+        if self.presentation.borrow().is_some() {
+            return;
+        }
+
+        let active = match self.pip.take() {
+            Some(pip) => {
+                if let Some(widget) = pip.take_video() {
+                    window.set_underlay(&widget);
+                }
+
+                pip.destroy();
+                window.present();
+                false
+            }
+            None => {
+                let pip = PipWindow::new(&app, &video);
+                if let Some(widget) = window.take_underlay() {
+                    pip.set_video(&widget);
+                }
+
+                pip.present();
+                *self.pip.borrow_mut() = Some(pip);
+                window.set_visible(false);
+                true
+            }
+        };
+
+        if let Some(ref webview) = *self.webview.borrow() {
+            let message = ipc::create_response(IpcEvent::Pip(active));
+            webview.send(&message);
+        }
+    }
+
+    /// Moves the video to a fullscreen window on the given monitor,
+    /// back to the main window when the monitor is unknown or empty
+    pub fn present_on(&self, id: &str) {
+        let app = self.obj();
+
+        let Some(window) = app.window() else {
+            return;
+        };
+
+        let monitor = presentation::monitors()
+            .into_iter()
+            .find(|monitor| presentation::monitor_id(monitor).as_deref() == Some(id));
+
+        // Moving the video to another window recreates its render context, which Video
+        // recovers from by selecting the video track again, so avoid needless moves
+        let presentation = self.presentation.take();
+        match (presentation, &monitor) {
+            (Some(presentation), Some(monitor)) => {
+                presentation.present_on(monitor);
+                *self.presentation.borrow_mut() = Some(presentation);
+            }
+            (Some(presentation), None) => {
+                if let Some(widget) = presentation.take_video() {
+                    window.set_underlay(&widget);
+                }
+
+                presentation.destroy();
+            }
+            (None, _) => {}
+        }
+
+        if let Some(ref monitor) = monitor
+            && self.presentation.borrow().is_none()
+        {
+            if self.pip.borrow().is_some() {
+                self.toggle_pip();
+            }
+
+            let presentation = PresentationWindow::new(&app);
+            if let Some(widget) = window.take_underlay() {
+                presentation.set_video(&widget);
+            }
+
+            presentation.present_on(monitor);
+            *self.presentation.borrow_mut() = Some(presentation);
+
+            // Keep browsing in the main window
+            window.present();
+        }
+
+        let id = monitor.as_ref().and_then(presentation::monitor_id);
+
+        if let Some(action) = app
+            .lookup_action("present-on")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_state(&id.clone().unwrap_or_default().to_variant());
+        }
+
+        if let Some(ref webview) = *self.webview.borrow() {
+            let message = ipc::create_response(IpcEvent::Presentation(id));
+            webview.send(&message);
+        }
+    }
+
+    fn send_monitors(&self) {
+        if let Some(ref webview) = *self.webview.borrow() {
+            let monitors = presentation::monitors()
+                .iter()
+                .filter_map(|monitor| {
+                    let id = presentation::monitor_id(monitor)?;
+                    let name = presentation::monitor_name(monitor);
+                    Some(serde_json::json!({ "id": id, "name": name }))
+                })
+                .collect_vec();
+
+            let message = ipc::create_response(IpcEvent::Monitors(Some(serde_json::json!({
+                "monitors": monitors,
+            }))));
+            webview.send(&message);
+        }
+    }
+
+    /// Playback state is lost with the page, so playback is stopped before reloading it.
+    /// The deeplink is replayed once the reloaded page is ready.
+    fn recover_webview(&self, reason: &str) {
+        error!("Web process {reason}");
+
+        if let Some(ref video) = *self.video.borrow() {
+            video.stop();
+            video.reset_observers();
+        }
+
+        let Some(webview) = self.webview.borrow().clone() else {
+            return;
+        };
+
+        let now = Instant::now();
+        let interval = Duration::from_secs(WEB_CRASH_INTERVAL);
+
+        let mut crashes = self.web_crashes.borrow_mut();
+        crashes.retain(|crash| now.duration_since(crash.time) < interval);
+        crashes.push(WebCrash {
+            time: now,
+            date: Utc::now(),
+            reason: reason.to_owned(),
+        });
+
+        match crashes.len() >= WEB_CRASH_LIMIT {
+            true => webview.show_error(self.web_diagnostics(&webview, &crashes)),
+            false => webview.reload(),
+        }
+    }
+
+    fn web_diagnostics(&self, webview: &WebView, crashes: &[WebCrash]) -> String {
+        let renderer = self
+            .video
+            .borrow()
+            .as_ref()
+            .map(|video| video.renderer().as_str())
+            .unwrap_or_default();
+
+        let mut lines = vec![
+            format!("{APP_NAME} {}", env!("CARGO_PKG_VERSION")),
+            format!(
+                "GTK {}.{}.{}",
+                gtk::major_version(),
+                gtk::minor_version(),
+                gtk::micro_version()
+            ),
+            format!("Renderer: {renderer}"),
+            format!("URL: {}", webview.uri().unwrap_or_default()),
+        ];
+
+        for crash in crashes {
+            lines.push(format!(
+                "{}: Web process {}",
+                crash.date.to_rfc3339(),
+                crash.reason
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    fn apply_show_override(&self) {
+        if let Some(ref video) = *self.video.borrow() {
+            let show_override = video_show_key(video)
+                .and_then(|key| self.settings.borrow().show_overrides.get(&key).cloned());
+
+            match show_override {
+                Some(show_override) => {
+                    video.set_video_zoom(show_override.zoom);
+                    video.set_video_aspect(show_override.aspect.as_deref());
+                    self.show_override_applied.set(true);
                 }
+                None if self.show_override_applied.replace(false) => {
+                    video.set_video_zoom(0.0);
+                    video.set_video_aspect(None);
+                }
+                None => {}
             }
         }
     }
+
+    pub fn save_show_override(&self) {
+        if let Some(ref video) = *self.video.borrow()
+            && let Some(key) = video_show_key(video)
+        {
+            let show_override = ShowOverride {
+                aspect: video.video_aspect(),
+                zoom: video.video_zoom(),
+            };
+
+            self.obj().update_settings(|settings| {
+                match show_override.aspect.is_none() && show_override.zoom == 0.0 {
+                    true => settings.show_overrides.remove(&key),
+                    false => settings.show_overrides.insert(key, show_override),
+                };
+            });
+            self.show_override_applied.set(true);
+        }
+    }
+
+    fn save_resume_position(&self) {
+        if self.resume_media.get()
+            && let Some(ref video) = *self.video.borrow()
+            && let Some(path) = video.path()
+        {
+            self.resume
+                .borrow_mut()
+                .record(&path, video.position(), video.duration());
+        }
+    }
+
+    fn offer_resume(&self, position: f64) {
+        self.withdraw_resume_offer();
+        self.resume_offer.set(Some(position));
+
+        if let Some(ref button) = *self.resume_offer_button.borrow() {
+            button.set_label(&format!(
+                "{} {}",
+                gettext("Resume from"),
+                resume::format_position(position)
+            ));
+            button.set_visible(true);
+        }
+
+        let timeout = glib::timeout_add_seconds_local_once(
+            RESUME_OFFER_TIMEOUT,
+            clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move || {
+                    imp.resume_offer_timeout.take();
+                    imp.withdraw_resume_offer();
+                }
+            ),
+        );
+        *self.resume_offer_timeout.borrow_mut() = Some(timeout);
+
+        self.send_resume_offer();
+    }
+
+    pub fn withdraw_resume_offer(&self) {
+        if let Some(timeout) = self.resume_offer_timeout.take() {
+            timeout.remove();
+        }
+
+        if let Some(ref button) = *self.resume_offer_button.borrow() {
+            button.set_visible(false);
+        }
+
+        if self.resume_offer.take().is_some() {
+            self.send_resume_offer();
+        }
+    }
+
+    fn send_resume_offer(&self) {
+        if let Some(ref webview) = *self.webview.borrow() {
+            let message = ipc::create_response(IpcEvent::ResumeAvailable(self.resume_offer.get()));
+            webview.send(&message);
+        }
+    }
+
+    /// Only polls the server while playing or while it keeps downloading afterwards
+    fn update_server_poll(&self) {
+        let playing = self
+            .video
+            .borrow()
+            .as_ref()
+            .is_some_and(|video| video.active());
+        if !playing && !self.server_active.get() {
+            if let Some(source) = self.server_poll.take() {
+                source.remove();
+            }
+
+            return;
+        }
+
+        if self.server_poll.borrow().is_some() {
+            return;
+        }
+
+        let source = glib::timeout_add_seconds_local(
+            SERVER_POLL_INTERVAL,
+            clone!(
+                #[weak(rename_to = imp)]
+                self,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || {
+                    spawn_local!(clone!(
+                        #[weak]
+                        imp,
+                        async move {
+                            match server::is_active().await {
+                                Ok(state) => {
+                                    imp.server_active.set(state);
+                                    imp.update_inhibit();
+                                    imp.update_server_poll();
+                                }
+                                Err(e) => debug!("Failed to get server activity: {e}"),
+                            }
+                        }
+                    ));
+
+                    glib::ControlFlow::Continue
+                }
+            ),
+        );
+
+        *self.server_poll.borrow_mut() = Some(source);
+    }
+
+    fn update_inhibit(&self) {
+        let playing = self.video.borrow().as_ref().is_some_and(|video| {
+            matches!(
+                video.playback_state(),
+                PlaybackState::Playing | PlaybackState::Buffering
+            )
+        });
+
+        if let Some(window) = self.obj().window() {
+            window.set_inhibit(playing, self.server_active.get());
+        }
+    }
+
+    pub fn open_media(&self, uri: String) {
+        let mut deeplink = self.deeplink.borrow_mut();
+        *deeplink = Some(uri.clone());
+        self.deeplink_opened.set(true);
+
+        if let Some(ref webview) = *self.webview.borrow() {
+            let message = ipc::create_response(IpcEvent::OpenMedia(uri));
+            webview.send(&message);
+        }
+    }
+}
+
+/// Streams are served from paths without a name, so the media title comes first
+fn video_show_key(video: &Video) -> Option<String> {
+    video
+        .media_title()
+        .as_deref()
+        .and_then(show_key)
+        .or_else(|| video.path().as_deref().and_then(show_key))
+}
+
+/// Episodes are matched by the part of their name before the episode number,
+/// such as `Show.Name.S01E02.mkv` or `Show Name 1x02`, the file name for paths
+fn show_key(name: &str) -> Option<String> {
+    let name = name.rsplit('/').next()?;
+    let name = glib::Uri::unescape_string(name, None::<&str>)
+        .map(String::from)
+        .unwrap_or(name.to_owned());
+
+    let words = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect_vec();
+
+    let episode = words.iter().position(|word| is_episode(word))?;
+    (episode > 0).then(|| words[..episode].join(" "))
+}
+
+fn is_episode(word: &str) -> bool {
+    let number = |value: &str| !value.is_empty() && value.chars().all(|c| c.is_ascii_digit());
+
+    word.strip_prefix('s')
+        .and_then(|word| word.split_once('e'))
+        .or_else(|| word.split_once('x'))
+        .is_some_and(|(season, episode)| number(season) && number(episode))
+}
+
+fn tray_playback(video: &Video) -> Option<TrayPlayback> {
+    video.active().then(|| TrayPlayback {
+        title: video.title(),
+        paused: video.paused(),
+        muted: video.muted(),
+        volume: video.volume(),
+    })
 }
 
 impl GtkApplicationImpl for Application {}
@@ -12,6 +12,21 @@ pub enum IpcEventMpv {
     Ended(Option<String>),
 }
 
+#[derive(Deserialize, Debug)]
+pub struct IpcNotificationAction {
+    pub label: String,
+    pub action: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IpcNotification {
+    pub id: Option<String>,
+    pub title: String,
+    pub body: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<IpcNotificationAction>,
+}
+
 #[derive(Deserialize, Debug)]
 pub enum IpcEvent {
     Init,
@@ -21,6 +36,25 @@ pub enum IpcEvent {
     Minimized(bool),
     Visibility(bool),
     OpenMedia(String),
+    Notify(IpcNotification),
+    NotificationAction((String, String)),
+    Downloads(Option<Value>),
+    DownloadChanged(Value),
+    DownloadCancel(u32),
+    ChapterSkippable(Option<String>),
+    SkipChapter,
+    SkipPatterns(Vec<String>),
+    ResumeAvailable(Option<f64>),
+    Resume,
+    ShaderPresets(Option<Value>),
+    ShaderPreset(String),
+    ShaderError(String),
+    Renderer(String),
+    Pip(bool),
+    TogglePip,
+    Monitors(Option<Value>),
+    PresentOn(String),
+    Presentation(Option<String>),
     Mpv(IpcEventMpv),
 }
 
@@ -1,7 +1,7 @@
 use serde::Deserialize;
 use serde_json::Value;
 
-use crate::app::ipc::event::{IpcEvent, IpcEventMpv};
+use crate::app::ipc::event::{IpcEvent, IpcEventMpv, IpcNotification};
 
 #[derive(Deserialize, Debug)]
 pub struct IpcMessageRequestWinSetVisilibty {
@@ -37,6 +37,29 @@ impl TryFrom<IpcMessageRequest> for IpcEvent {
 
                                 Ok(IpcEvent::Fullscreen(data.fullscreen))
                             }
+                            "notify" => {
+                                let data: IpcNotification = serde_json::from_value(data)
+                                    .map_err(|_| "Invalid notify object")?;
+
+                                Ok(IpcEvent::Notify(data))
+                            }
+                            "download-cancel" => {
+                                let id = data.as_u64().ok_or("Invalid download-cancel id")?;
+                                Ok(IpcEvent::DownloadCancel(id as u32))
+                            }
+                            "shader-preset" => {
+                                let name = data.as_str().ok_or("Invalid shader-preset name")?;
+                                Ok(IpcEvent::ShaderPreset(name.to_owned()))
+                            }
+                            "present-on" => {
+                                let id = data.as_str().ok_or("Invalid present-on monitor")?;
+                                Ok(IpcEvent::PresentOn(id.to_owned()))
+                            }
+                            "skip-patterns" => {
+                                let patterns: Vec<String> = serde_json::from_value(data)
+                                    .map_err(|_| "Invalid skip-patterns list")?;
+                                Ok(IpcEvent::SkipPatterns(patterns))
+                            }
                             "mpv-command" => {
                                 let data: Vec<String> = serde_json::from_value(data)
                                     .expect("Invalid mpv-command arguments");
@@ -73,6 +96,12 @@ impl TryFrom<IpcMessageRequest> for IpcEvent {
                         },
                         None => match name {
                             "quit" => Ok(IpcEvent::Quit),
+                            "downloads" => Ok(IpcEvent::Downloads(None)),
+                            "skip-chapter" => Ok(IpcEvent::SkipChapter),
+                            "resume" => Ok(IpcEvent::Resume),
+                            "shader-presets" => Ok(IpcEvent::ShaderPresets(None)),
+                            "pip" => Ok(IpcEvent::TogglePip),
+                            "monitors" => Ok(IpcEvent::Monitors(None)),
                             _ => Err("Unknown method"),
                         },
                     }
@@ -85,6 +85,119 @@ impl TryFrom<IpcEvent> for IpcMessageResponse {
                 data: None,
                 args: Some(json!(["open-media", deeplink])),
             }),
+            IpcEvent::NotificationAction((id, action)) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!([
+                    "notification-action",
+                    {
+                        "id": id,
+                        "action": action,
+                    }
+                ])),
+            }),
+            IpcEvent::Downloads(Some(downloads)) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!(["downloads", downloads])),
+            }),
+            IpcEvent::DownloadChanged(download) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!(["download-changed", download])),
+            }),
+            IpcEvent::ChapterSkippable(title) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!([
+                    "chapter-skippable",
+                    {
+                        "title": title,
+                    }
+                ])),
+            }),
+            IpcEvent::ResumeAvailable(position) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!([
+                    "resume-available",
+                    {
+                        "position": position,
+                    }
+                ])),
+            }),
+            IpcEvent::ShaderPresets(Some(presets)) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!(["shader-presets", presets])),
+            }),
+            IpcEvent::ShaderError(error) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!([
+                    "shader-error",
+                    {
+                        "error": error,
+                    }
+                ])),
+            }),
+            IpcEvent::Renderer(renderer) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!([
+                    "renderer-changed",
+                    {
+                        "renderer": renderer,
+                    }
+                ])),
+            }),
+            IpcEvent::Pip(active) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!([
+                    "pip-changed",
+                    {
+                        "active": active,
+                    }
+                ])),
+            }),
+            IpcEvent::Monitors(Some(monitors)) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!(["monitors", monitors])),
+            }),
+            IpcEvent::Presentation(monitor) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!([
+                    "presentation-changed",
+                    {
+                        "monitor": monitor,
+                    }
+                ])),
+            }),
             IpcEvent::Mpv(IpcEventMpv::Change((name, value))) => Ok(IpcMessageResponse {
                 id: 1,
                 r#type: 1,
@@ -1,23 +1,98 @@
 mod about;
 mod config;
+mod downloads;
 mod imp;
 mod ipc;
+mod notifier;
+mod pip;
+mod presentation;
+mod resume;
+pub mod settings;
+mod shaders;
+mod stats;
 mod tray;
 mod video;
 mod webview;
 mod window;
 
+use adw::subclass::prelude::*;
+use anyhow::{Context, bail};
+use gettextrs::gettext;
 use gtk::{
-    gio::{self, ActionEntry, ApplicationFlags, prelude::*},
-    glib::{self, ExitCode, Object},
+    gio::{self, ActionEntry, ApplicationFlags, Cancellable, prelude::*},
+    glib::{self, ExitCode, Object, Variant, VariantTy},
     prelude::*,
 };
+use tokio::sync::mpsc::UnboundedReceiver;
+use tracing::error;
 
-use crate::app::{
-    about::AboutDialog,
-    config::{APP_ID, APP_NAME},
+use crate::{
+    app::{
+        about::AboutDialog,
+        config::{
+            APP_ID, APP_NAME, AUTO_AUDIO_DEVICE, DEFAULT_AUDIO_CHANNELS, SERVER_NOTIFICATION_ID,
+        },
+        ipc::{self, event::IpcEvent},
+        presentation,
+        settings::{CloseAction, HiddenPlayback, MonitorPreference, Settings},
+        video::Video,
+        webview::WebView,
+        window::Window,
+    },
+    server::ServerEvent,
+    spawn_local,
 };
 
+fn toggle_action(
+    name: &str,
+    state: bool,
+    apply: fn(&Application, bool),
+) -> ActionEntry<Application> {
+    ActionEntry::builder(name)
+        .state(state.to_variant())
+        .activate(move |app: &Application, action, _| {
+            let state = !action
+                .state()
+                .and_then(|state| state.get::<bool>())
+                .unwrap_or_default();
+            action.set_state(&state.to_variant());
+
+            apply(app, state);
+        })
+        .build()
+}
+
+fn choice_action(
+    name: &str,
+    state: &str,
+    apply: fn(&Application, &str),
+) -> ActionEntry<Application> {
+    ActionEntry::builder(name)
+        .parameter_type(Some(VariantTy::STRING))
+        .state(state.to_variant())
+        .activate(move |app: &Application, action, parameter| {
+            if let Some(value) = parameter.and_then(|value| value.str()) {
+                action.set_state(&value.to_variant());
+
+                apply(app, value);
+            }
+        })
+        .build()
+}
+
+fn adjust_action(name: &str, property: &'static str) -> ActionEntry<Application> {
+    ActionEntry::builder(name)
+        .parameter_type(Some(VariantTy::DOUBLE))
+        .activate(move |app: &Application, _, parameter| {
+            if let Some(video) = app.video()
+                && let Some(delta) = parameter.and_then(|value| value.get::<f64>())
+            {
+                video.adjust(property, delta);
+            }
+        })
+        .build()
+}
+
 glib::wrapper! {
     pub struct Application(ObjectSubclass<imp::Application>)
     @extends gio::Application, gtk::Application, adw::Application,
@@ -61,10 +136,527 @@ impl Application {
             })
             .build();
 
-        self.add_action_entries([quit_action, show_about_action]);
+        let settings = self.imp().settings.borrow().clone();
+
+        let close_action_action = choice_action(
+            "close-action",
+            settings.close_action.as_str(),
+            |app, value| match value.parse::<CloseAction>() {
+                Ok(close_action) => {
+                    app.update_settings(|settings| settings.close_action = close_action);
+                }
+                Err(e) => error!("Failed to set close action: {e}"),
+            },
+        );
+
+        let hidden_playback_action = choice_action(
+            "hidden-playback",
+            settings.hidden_playback.as_str(),
+            |app, value| match value.parse::<HiddenPlayback>() {
+                Ok(hidden_playback) => {
+                    app.update_settings(|settings| settings.hidden_playback = hidden_playback);
+                }
+                Err(e) => error!("Failed to set hidden playback: {e}"),
+            },
+        );
+
+        let autostart_action = toggle_action("autostart", settings.autostart, |app, state| {
+            app.update_settings(|settings| settings.autostart = state);
+
+            if let Some(window) = app.window() {
+                window.request_backgound(state);
+            }
+        });
+
+        let ask_download_location_action = toggle_action(
+            "ask-download-location",
+            settings.ask_download_location,
+            |app, state| {
+                app.update_settings(|settings| settings.ask_download_location = state);
+            },
+        );
+
+        let audio_device = settings
+            .audio_device
+            .as_deref()
+            .unwrap_or(AUTO_AUDIO_DEVICE);
+        let audio_device_action = choice_action("audio-device", audio_device, |app, name| {
+            app.update_settings(|settings| {
+                settings.audio_device = (name != AUTO_AUDIO_DEVICE).then(|| name.to_owned());
+            });
+            app.imp().apply_audio_device();
+        });
+
+        let audio_spdif_action =
+            choice_action("audio-spdif", &settings.audio_spdif, |app, codecs| {
+                app.update_settings(|settings| settings.audio_spdif = codecs.to_owned());
+                app.imp().apply_audio_settings();
+            });
+
+        let audio_channels = settings
+            .audio_channels
+            .as_deref()
+            .unwrap_or(DEFAULT_AUDIO_CHANNELS);
+        let audio_channels_action =
+            choice_action("audio-channels", audio_channels, |app, channels| {
+                app.update_settings(|settings| {
+                    settings.audio_channels =
+                        (channels != DEFAULT_AUDIO_CHANNELS).then(|| channels.to_owned());
+                });
+                app.imp().apply_audio_settings();
+            });
+
+        let audio_exclusive_action =
+            toggle_action("audio-exclusive", settings.audio_exclusive, |app, state| {
+                app.update_settings(|settings| settings.audio_exclusive = state);
+                app.imp().apply_audio_settings();
+            });
+
+        let night_mode_action = toggle_action("night-mode", settings.night_mode, |app, state| {
+            app.update_settings(|settings| settings.night_mode = state);
+            app.imp().apply_audio_settings();
+        });
+
+        let skip_chapter_action = ActionEntry::builder("skip-chapter")
+            .activate(|app: &Self, _, _| {
+                if let Some(video) = app.video() {
+                    video.skip_chapter();
+                }
+            })
+            .build();
+
+        let resume_action = ActionEntry::builder("resume")
+            .activate(|app: &Self, _, _| {
+                if let Some(video) = app.video()
+                    && let Some(position) = app.imp().resume_offer.get()
+                {
+                    video.seek_to(position);
+                    app.imp().withdraw_resume_offer();
+                }
+            })
+            .build();
+
+        let auto_skip_action = toggle_action("auto-skip", settings.auto_skip, |app, state| {
+            app.update_settings(|settings| settings.auto_skip = state);
+        });
+
+        self.add_action_entries([
+            quit_action,
+            show_about_action,
+            close_action_action,
+            hidden_playback_action,
+            autostart_action,
+            ask_download_location_action,
+            audio_device_action,
+            audio_spdif_action,
+            audio_channels_action,
+            audio_exclusive_action,
+            night_mode_action,
+            skip_chapter_action,
+            resume_action,
+            auto_skip_action,
+        ]);
+
+        if let Some(action) = self
+            .lookup_action("skip-chapter")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_enabled(false);
+        }
+    }
+
+    fn setup_video_actions(&self) {
+        let zoom_action = ActionEntry::builder("video-zoom")
+            .parameter_type(Some(VariantTy::DOUBLE))
+            .activate(|app: &Self, _, parameter| {
+                if let Some(video) = app.video()
+                    && let Some(delta) = parameter.and_then(|value| value.get::<f64>())
+                {
+                    video.adjust("video-zoom", delta);
+                    app.imp().save_show_override();
+                }
+            })
+            .build();
+
+        let aspect_action = ActionEntry::builder("video-aspect")
+            .activate(|app: &Self, _, _| {
+                if let Some(video) = app.video() {
+                    video.cycle_video_aspect();
+                    app.imp().save_show_override();
+                }
+            })
+            .build();
+
+        let rotate_action = ActionEntry::builder("video-rotate")
+            .activate(|app: &Self, _, _| {
+                if let Some(video) = app.video() {
+                    video.rotate_video();
+                }
+            })
+            .build();
+
+        let deinterlace_action = ActionEntry::builder("video-deinterlace")
+            .activate(|app: &Self, _, _| {
+                if let Some(video) = app.video() {
+                    video.toggle_deinterlace();
+                }
+            })
+            .build();
+
+        let auto_crop = self.imp().settings.borrow().auto_crop;
+        let auto_crop_action = toggle_action("auto-crop", auto_crop, |app, state| {
+            app.update_settings(|settings| settings.auto_crop = state);
+
+            if let Some(video) = app.video() {
+                video.set_auto_crop(state);
+            }
+        });
+
+        let detect_crop_action = ActionEntry::builder("video-detect-crop")
+            .activate(|app: &Self, _, _| {
+                if let Some(video) = app.video() {
+                    video.detect_crop();
+                }
+            })
+            .build();
+
+        let shader_preset = self.imp().settings.borrow().shader_preset.clone();
+        let shader_preset_action = choice_action(
+            "shader-preset",
+            shader_preset.as_deref().unwrap_or_default(),
+            |app, name| {
+                app.update_settings(|settings| {
+                    settings.shader_preset = (!name.is_empty()).then(|| name.to_owned());
+                });
+                app.imp().apply_shader_preset();
+            },
+        );
+
+        let fit_window = self.imp().settings.borrow().fit_window;
+        let fit_window_action = toggle_action("fit-window", fit_window, |app, state| {
+            app.update_settings(|settings| settings.fit_window = state);
+        });
+
+        let lock_aspect = self.imp().settings.borrow().lock_aspect;
+        let lock_aspect_action = toggle_action("lock-aspect", lock_aspect, |app, state| {
+            app.update_settings(|settings| settings.lock_aspect = state);
+            app.imp().apply_aspect_lock();
+        });
+
+        let fullscreen_monitor = self
+            .imp()
+            .settings
+            .borrow()
+            .fullscreen_monitor
+            .as_ref()
+            .and_then(presentation::find_monitor)
+            .and_then(|monitor| presentation::monitor_id(&monitor))
+            .unwrap_or_default();
+        let fullscreen_monitor_action =
+            choice_action("fullscreen-monitor", &fullscreen_monitor, |app, id| {
+                let preference = presentation::monitors()
+                    .into_iter()
+                    .find(|monitor| presentation::monitor_id(monitor).as_deref() == Some(id))
+                    .map(|monitor| MonitorPreference {
+                        connector: monitor.connector().map(|connector| connector.to_string()),
+                        model: monitor.model().map(|model| model.to_string()),
+                    });
+                app.update_settings(|settings| settings.fullscreen_monitor = preference);
+
+                // Move over right away when already fullscreen
+                if let Some(window) = app.window()
+                    && window.is_fullscreen()
+                {
+                    window.set_fullscreen(true);
+                }
+            });
+
+        let window_size_action = ActionEntry::builder("window-size")
+            .parameter_type(Some(VariantTy::DOUBLE))
+            .activate(|app: &Self, _, parameter| {
+                if let Some(scale) = parameter.and_then(|value| value.get::<f64>()) {
+                    app.imp().fit_window(scale);
+                }
+            })
+            .build();
+
+        let reset_action = ActionEntry::builder("video-reset")
+            .activate(|app: &Self, _, _| {
+                if let Some(video) = app.video() {
+                    video.reset_video();
+                    app.imp().save_show_override();
+                }
+            })
+            .build();
+
+        self.add_action_entries([
+            zoom_action,
+            adjust_action("video-pan-x", "video-pan-x"),
+            adjust_action("video-pan-y", "video-pan-y"),
+            adjust_action("video-brightness", "brightness"),
+            adjust_action("video-contrast", "contrast"),
+            adjust_action("video-saturation", "saturation"),
+            adjust_action("video-gamma", "gamma"),
+            aspect_action,
+            rotate_action,
+            deinterlace_action,
+            auto_crop_action,
+            detect_crop_action,
+            shader_preset_action,
+            fit_window_action,
+            lock_aspect_action,
+            fullscreen_monitor_action,
+            window_size_action,
+            reset_action,
+        ]);
+    }
+
+    pub fn close_action(&self) -> CloseAction {
+        let app = self.imp();
+
+        let close_action = app.settings.borrow().close_action;
+        let tray_available = app
+            .tray
+            .borrow()
+            .as_ref()
+            .is_some_and(|tray| tray.available());
+
+        match close_action {
+            CloseAction::Tray if !tray_available => CloseAction::Quit,
+            close_action => close_action,
+        }
+    }
+
+    fn setup_remote_actions(&self) {
+        let play_pause_action = ActionEntry::builder("play-pause")
+            .activate(|app: &Self, _, _| {
+                if let Some(video) = app.video() {
+                    video.play_pause();
+                }
+            })
+            .build();
+
+        let stop_action = ActionEntry::builder("stop")
+            .activate(|app: &Self, _, _| {
+                if let Some(video) = app.video() {
+                    video.stop();
+                }
+            })
+            .build();
+
+        let seek_action = ActionEntry::builder("seek")
+            .parameter_type(Some(VariantTy::DOUBLE))
+            .activate(|app: &Self, _, parameter| {
+                if let Some(video) = app.video()
+                    && let Some(offset) = parameter.and_then(|value| value.get::<f64>())
+                {
+                    video.seek(offset);
+                }
+            })
+            .build();
+
+        let set_volume_action = ActionEntry::builder("set-volume")
+            .parameter_type(Some(VariantTy::DOUBLE))
+            .activate(|app: &Self, _, parameter| {
+                if let Some(video) = app.video()
+                    && let Some(volume) = parameter.and_then(|value| value.get::<f64>())
+                {
+                    video.set_volume(volume);
+                }
+            })
+            .build();
+
+        let toggle_fullscreen_action = ActionEntry::builder("toggle-fullscreen")
+            .activate(|app: &Self, _, _| {
+                if let Some(window) = app.window() {
+                    let state = !window.is_fullscreen();
+                    window.set_fullscreen(state);
+
+                    if let Some(webview) = app.webview() {
+                        let message = ipc::create_response(IpcEvent::Fullscreen(state));
+                        webview.send(&message);
+                    }
+                }
+            })
+            .build();
+
+        let toggle_pip_action = ActionEntry::builder("toggle-pip")
+            .activate(|app: &Self, _, _| {
+                app.imp().toggle_pip();
+            })
+            .build();
+
+        let present_on_action = ActionEntry::builder("present-on")
+            .parameter_type(Some(VariantTy::STRING))
+            .state("".to_variant())
+            .activate(|app: &Self, _, parameter| {
+                if let Some(id) = parameter.and_then(|value| value.str()) {
+                    app.imp().present_on(id);
+                }
+            })
+            .build();
+
+        let open_uri_action = ActionEntry::builder("open-uri")
+            .parameter_type(Some(VariantTy::STRING))
+            .activate(|app: &Self, _, parameter| {
+                if let Some(uri) = parameter.and_then(|value| value.get::<String>()) {
+                    app.activate();
+
+                    if let Some(window) = app.window() {
+                        let app_weak = app.downgrade();
+                        window.open_media_uri(uri, move |uri| {
+                            if let Some(app) = app_weak.upgrade() {
+                                app.imp().open_media(uri);
+                            }
+                        });
+                    }
+                }
+            })
+            .build();
+
+        let show_action = ActionEntry::builder("show")
+            .activate(|app: &Self, _, _| {
+                if let Some(window) = app.window() {
+                    window.present();
+                }
+            })
+            .build();
+
+        let hide_action = ActionEntry::builder("hide")
+            .activate(|app: &Self, _, _| {
+                if let Some(window) = app.window() {
+                    window.set_visible(false);
+                }
+            })
+            .build();
+
+        self.add_action_entries([
+            play_pause_action,
+            stop_action,
+            seek_action,
+            set_volume_action,
+            toggle_fullscreen_action,
+            toggle_pip_action,
+            present_on_action,
+            open_uri_action,
+            show_action,
+            hide_action,
+        ]);
+    }
+
+    pub fn activate_remote_action(
+        &self,
+        name: &str,
+        parameter: Option<&Variant>,
+    ) -> anyhow::Result<()> {
+        self.register(Cancellable::NONE)
+            .context("Failed to register application")?;
+
+        if !self.is_remote() {
+            bail!("{APP_NAME} is not running");
+        }
+
+        if !self.has_action(name) {
+            bail!("{APP_NAME} does not support the {name} action");
+        }
+
+        self.activate_action(name, parameter);
+
+        if let Some(connection) = self.dbus_connection() {
+            connection
+                .flush_sync(Cancellable::NONE)
+                .context("Failed to send action")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn watch_server(&self, mut events: UnboundedReceiver<ServerEvent>) {
+        let app = self.downgrade();
+
+        spawn_local!(async move {
+            while let Some(event) = events.recv().await {
+                let Some(app) = app.upgrade() else {
+                    break;
+                };
+
+                if let Some(ref notifier) = *app.imp().notifier.borrow() {
+                    match event {
+                        ServerEvent::Updated(version) => notifier.send(
+                            SERVER_NOTIFICATION_ID,
+                            &gettext("Streaming server updated"),
+                            Some(version.as_str()),
+                            &[],
+                        ),
+                        ServerEvent::Stopped => notifier.send(
+                            SERVER_NOTIFICATION_ID,
+                            &gettext("Streaming server stopped"),
+                            Some(
+                                gettext("Playback will not work until Stremio is restarted")
+                                    .as_str(),
+                            ),
+                            &[(gettext("Quit").as_str(), "quit")],
+                        ),
+                    }
+                }
+            }
+        });
+    }
+
+    fn update_settings<F: FnOnce(&mut Settings)>(&self, f: F) {
+        let mut settings = self.imp().settings.borrow_mut();
+        f(&mut settings);
+        settings.save();
+    }
+
+    /// Main window, which is not the active one while the mini player is shown
+    fn window(&self) -> Option<Window> {
+        self.windows()
+            .into_iter()
+            .find_map(|window| window.downcast::<Window>().ok())
+    }
+
+    fn video(&self) -> Option<Video> {
+        self.imp().video.borrow().clone()
+    }
+
+    fn webview(&self) -> Option<WebView> {
+        self.imp().webview.borrow().clone()
     }
 
     fn setup_accels(&self) {
         self.set_accels_for_action("app.quit", &["<Control>q"]);
+        self.set_accels_for_action("win.toggle-stats", &["<Control>i"]);
+        self.set_accels_for_action("app.toggle-pip", &["<Control><Shift>p"]);
+
+        for (action, accels) in [
+            (
+                "app.video-zoom(0.1)",
+                &["<Alt>plus", "<Alt>equal", "<Alt>KP_Add"][..],
+            ),
+            ("app.video-zoom(-0.1)", &["<Alt>minus", "<Alt>KP_Subtract"]),
+            ("app.video-pan-x(0.1)", &["<Alt>Left"]),
+            ("app.video-pan-x(-0.1)", &["<Alt>Right"]),
+            ("app.video-pan-y(0.1)", &["<Alt>Up"]),
+            ("app.video-pan-y(-0.1)", &["<Alt>Down"]),
+            ("app.video-contrast(-1.0)", &["<Control><Alt>1"]),
+            ("app.video-contrast(1.0)", &["<Control><Alt>2"]),
+            ("app.video-brightness(-1.0)", &["<Control><Alt>3"]),
+            ("app.video-brightness(1.0)", &["<Control><Alt>4"]),
+            ("app.video-gamma(-1.0)", &["<Control><Alt>5"]),
+            ("app.video-gamma(1.0)", &["<Control><Alt>6"]),
+            ("app.video-saturation(-1.0)", &["<Control><Alt>7"]),
+            ("app.video-saturation(1.0)", &["<Control><Alt>8"]),
+            ("app.video-aspect", &["<Alt>a"]),
+            ("app.video-rotate", &["<Alt>r"]),
+            ("app.video-deinterlace", &["<Alt>d"]),
+            ("app.video-detect-crop", &["<Alt>c"]),
+            ("app.video-reset", &["<Alt>BackSpace"]),
+            ("app.window-size(0.5)", &["<Alt>0"]),
+            ("app.window-size(1.0)", &["<Alt>1"]),
+            ("app.window-size(2.0)", &["<Alt>2"]),
+        ] {
+            self.set_accels_for_action(action, accels);
+        }
     }
 }
@@ -0,0 +1,99 @@
+use std::sync::{Arc, OnceLock};
+
+use ashpd::desktop::notification::{Notification, NotificationProxy};
+use futures_util::StreamExt;
+use gtk::{
+    glib::{self, object::ObjectExt, subclass::Signal, types::StaticType},
+    subclass::prelude::*,
+};
+use tokio::sync::Mutex;
+use tracing::{error, warn};
+
+use crate::spawn_local;
+
+#[derive(Default)]
+pub struct Notifier {
+    proxy: Arc<Mutex<Option<Arc<NotificationProxy<'static>>>>>,
+}
+
+impl Notifier {
+    pub fn send(&self, id: String, notification: Notification) {
+        let proxy = self.proxy.clone();
+        spawn_local!(async move {
+            let proxy = proxy.lock().await;
+            if let Some(proxy) = proxy.as_ref() {
+                proxy
+                    .add_notification(&id, notification)
+                    .await
+                    .map_err(|e| error!("Failed to send notification: {e}"))
+                    .ok();
+            }
+        });
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for Notifier {
+    const NAME: &'static str = "Notifier";
+    type Type = super::Notifier;
+    type ParentType = glib::Object;
+}
+
+impl ObjectImpl for Notifier {
+    fn signals() -> &'static [Signal] {
+        static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+        SIGNALS.get_or_init(|| {
+            vec![
+                Signal::builder("action")
+                    .param_types([
+                        String::static_type(),
+                        String::static_type(),
+                        String::static_type(),
+                    ])
+                    .build(),
+            ]
+        })
+    }
+
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let local_proxy = self.proxy.clone();
+        let object_weak = self.obj().downgrade();
+        spawn_local!(async move {
+            // Held until the proxy is created so that notifications sent meanwhile wait for it
+            let mut proxy_guard = local_proxy.lock().await;
+            let proxy = match NotificationProxy::new().await {
+                Ok(proxy) => Arc::new(proxy),
+                Err(e) => {
+                    warn!("Notifications are not available: {e}");
+                    return;
+                }
+            };
+            *proxy_guard = Some(proxy.clone());
+            drop(proxy_guard);
+
+            let mut actions = match proxy.receive_action_invoked().await {
+                Ok(actions) => actions,
+                Err(e) => {
+                    error!("Failed to listen for notification actions: {e}");
+                    return;
+                }
+            };
+
+            while let Some(action) = actions.next().await {
+                let Some(object) = object_weak.upgrade() else {
+                    break;
+                };
+
+                let target = action
+                    .parameter()
+                    .first()
+                    .and_then(|value| value.downcast_ref::<&str>().ok())
+                    .map(str::to_owned);
+
+                object.emit_by_name::<()>("action", &[&action.id(), &action.name(), &target]);
+            }
+        });
+    }
+}
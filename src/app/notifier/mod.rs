@@ -0,0 +1,38 @@
+mod imp;
+
+use adw::subclass::prelude::ObjectSubclassIsExt;
+use ashpd::desktop::notification::{Button, Notification};
+use gtk::glib::{self, closure_local, object::ObjectExt};
+
+glib::wrapper! {
+    pub struct Notifier(ObjectSubclass<imp::Notifier>);
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        glib::Object::builder().build()
+    }
+}
+
+impl Notifier {
+    pub fn send(&self, id: &str, title: &str, body: Option<&str>, buttons: &[(&str, &str)]) {
+        let notification = buttons.iter().fold(
+            Notification::new(title).body(body).default_action("show"),
+            |notification, (label, action)| notification.button(Button::new(label, action)),
+        );
+
+        self.imp().send(id.to_owned(), notification);
+    }
+
+    pub fn connect_action<T: Fn(&str, &str, Option<&str>) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "action",
+            false,
+            closure_local!(
+                move |_: Notifier, id: &str, name: &str, target: Option<&str>| {
+                    callback(id, name, target);
+                }
+            ),
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub const DEFAULT_WIDTH: i32 = 480;
+pub const DEFAULT_HEIGHT: i32 = 270;
+pub const SEEK_OFFSET: f64 = 10.0;
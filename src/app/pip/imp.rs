@@ -0,0 +1,104 @@
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::{
+    glib::{self, Propagation, clone},
+    prelude::*,
+};
+
+use crate::app::pip::config::{DEFAULT_HEIGHT, DEFAULT_WIDTH, SEEK_OFFSET};
+
+#[derive(Default)]
+pub struct PipWindow {
+    pub overlay: gtk::Overlay,
+    pub play_pause_button: gtk::Button,
+    controls: gtk::Revealer,
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for PipWindow {
+    const NAME: &'static str = "PipWindow";
+    type Type = super::PipWindow;
+    type ParentType = gtk::Window;
+}
+
+impl ObjectImpl for PipWindow {
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let object = self.obj();
+        object.set_title(Some(&gettext("Picture in Picture")));
+        object.set_decorated(false);
+        object.set_default_size(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+
+        let seek_backward_button = gtk::Button::builder()
+            .icon_name("media-seek-backward-symbolic")
+            .tooltip_text(gettext("Seek backward"))
+            .action_name("app.seek")
+            .action_target(&(-SEEK_OFFSET).to_variant())
+            .build();
+
+        self.play_pause_button
+            .set_action_name(Some("app.play-pause"));
+
+        let seek_forward_button = gtk::Button::builder()
+            .icon_name("media-seek-forward-symbolic")
+            .tooltip_text(gettext("Seek forward"))
+            .action_name("app.seek")
+            .action_target(&SEEK_OFFSET.to_variant())
+            .build();
+
+        let back_button = gtk::Button::builder()
+            .icon_name("view-restore-symbolic")
+            .tooltip_text(gettext("Back to Stremio"))
+            .action_name("app.toggle-pip")
+            .build();
+
+        let controls = gtk::Box::builder()
+            .spacing(6)
+            .halign(gtk::Align::Center)
+            .margin_bottom(12)
+            .css_classes(["osd", "toolbar"])
+            .build();
+        controls.append(&seek_backward_button);
+        controls.append(&self.play_pause_button);
+        controls.append(&seek_forward_button);
+        controls.append(&back_button);
+
+        self.controls.set_child(Some(&controls));
+        self.controls
+            .set_transition_type(gtk::RevealerTransitionType::Crossfade);
+        self.controls.set_valign(gtk::Align::End);
+        self.overlay.add_overlay(&self.controls);
+
+        // Controls are only shown while hovered to keep the video unobstructed
+        let motion = gtk::EventControllerMotion::new();
+        motion.connect_enter(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |_, _, _| {
+                imp.controls.set_reveal_child(true);
+            }
+        ));
+        motion.connect_leave(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |_| {
+                imp.controls.set_reveal_child(false);
+            }
+        ));
+        object.add_controller(motion);
+
+        let handle = gtk::WindowHandle::builder().child(&self.overlay).build();
+        object.set_child(Some(&handle));
+    }
+}
+
+impl WidgetImpl for PipWindow {}
+
+impl WindowImpl for PipWindow {
+    fn close_request(&self) -> Propagation {
+        self.obj().activate_action("app.toggle-pip", None).ok();
+
+        Propagation::Stop
+    }
+}
@@ -0,0 +1,50 @@
+mod config;
+mod imp;
+
+use adw::subclass::prelude::*;
+use gtk::{
+    Widget, gio,
+    glib::{self, object::IsA},
+    prelude::*,
+};
+
+use crate::app::{Application, video::Video};
+
+glib::wrapper! {
+    pub struct PipWindow(ObjectSubclass<imp::PipWindow>)
+    @extends gtk::Window, gtk::Widget,
+    @implements gio::ActionGroup, gio::ActionMap, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager, gtk::Native, gtk::Root;
+}
+
+impl PipWindow {
+    pub fn new(application: &Application, video: &Video) -> Self {
+        let pip: Self = glib::Object::builder()
+            .property("application", application)
+            .build();
+
+        video
+            .bind_property("paused", &pip.imp().play_pause_button, "icon-name")
+            .transform_to(|_, paused: bool| {
+                Some(match paused {
+                    true => "media-playback-start-symbolic",
+                    false => "media-playback-pause-symbolic",
+                })
+            })
+            .sync_create()
+            .build();
+
+        pip
+    }
+
+    pub fn set_video(&self, widget: &impl IsA<Widget>) {
+        self.imp().overlay.set_child(Some(widget));
+    }
+
+    pub fn take_video(&self) -> Option<Widget> {
+        let overlay = &self.imp().overlay;
+
+        let widget = overlay.child();
+        overlay.set_child(None::<&Widget>);
+        widget
+    }
+}
@@ -0,0 +1,57 @@
+use std::cell::RefCell;
+
+use adw::subclass::prelude::*;
+use gtk::{
+    gdk,
+    glib::{self, Propagation},
+    prelude::*,
+};
+
+#[derive(Default)]
+pub struct PresentationWindow {
+    pub monitor: RefCell<Option<gdk::Monitor>>,
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for PresentationWindow {
+    const NAME: &'static str = "PresentationWindow";
+    type Type = super::PresentationWindow;
+    type ParentType = gtk::Window;
+}
+
+impl ObjectImpl for PresentationWindow {
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let object = self.obj();
+        object.set_decorated(false);
+
+        let keys = gtk::EventControllerKey::new();
+        keys.connect_key_pressed(|controller, key, _, _| {
+            if key != gdk::Key::Escape {
+                return Propagation::Proceed;
+            }
+
+            if let Some(widget) = controller.widget() {
+                widget
+                    .activate_action("app.present-on", Some(&"".to_variant()))
+                    .ok();
+            }
+
+            Propagation::Stop
+        });
+        object.add_controller(keys);
+    }
+}
+
+impl WidgetImpl for PresentationWindow {}
+
+impl WindowImpl for PresentationWindow {
+    fn close_request(&self) -> Propagation {
+        self.obj()
+            .activate_action("app.present-on", Some(&"".to_variant()))
+            .ok();
+
+        Propagation::Stop
+    }
+}
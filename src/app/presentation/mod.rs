@@ -0,0 +1,98 @@
+mod imp;
+
+use adw::subclass::prelude::*;
+use gtk::{
+    Widget, gdk, gio,
+    glib::{self, object::IsA},
+    prelude::*,
+};
+
+use crate::app::{Application, settings::MonitorPreference};
+
+glib::wrapper! {
+    pub struct PresentationWindow(ObjectSubclass<imp::PresentationWindow>)
+    @extends gtk::Window, gtk::Widget,
+    @implements gio::ActionGroup, gio::ActionMap, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager, gtk::Native, gtk::Root;
+}
+
+impl PresentationWindow {
+    pub fn new(application: &Application) -> Self {
+        glib::Object::builder()
+            .property("application", application)
+            .build()
+    }
+
+    pub fn set_video(&self, widget: &impl IsA<Widget>) {
+        self.set_child(Some(widget));
+    }
+
+    pub fn take_video(&self) -> Option<Widget> {
+        let widget = self.child();
+        self.set_child(None::<&Widget>);
+        widget
+    }
+
+    pub fn present_on(&self, monitor: &gdk::Monitor) {
+        *self.imp().monitor.borrow_mut() = Some(monitor.clone());
+
+        self.fullscreen_on_monitor(monitor);
+        self.present();
+    }
+
+    /// Whether the monitor presented on was unplugged
+    pub fn monitor_lost(&self) -> bool {
+        !self
+            .imp()
+            .monitor
+            .borrow()
+            .as_ref()
+            .is_some_and(|monitor| monitor.is_valid())
+    }
+}
+
+/// Name used to find a monitor again, the connector when the backend reports one
+pub fn monitor_id(monitor: &gdk::Monitor) -> Option<String> {
+    monitor
+        .connector()
+        .or_else(|| monitor.description())
+        .map(|id| id.to_string())
+}
+
+pub fn monitor_name(monitor: &gdk::Monitor) -> String {
+    monitor
+        .description()
+        .or_else(|| monitor.model())
+        .or_else(|| monitor.connector())
+        .map(|name| name.to_string())
+        .unwrap_or_default()
+}
+
+pub fn monitors() -> Vec<gdk::Monitor> {
+    gdk::Display::default()
+        .map(|display| {
+            display
+                .monitors()
+                .iter::<gdk::Monitor>()
+                .filter_map(Result::ok)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn find_monitor(preference: &MonitorPreference) -> Option<gdk::Monitor> {
+    let monitors = monitors();
+
+    let matches = |value: Option<glib::GString>, preferred: &Option<String>| {
+        preferred.is_some() && value.map(|value| value.to_string()) == *preferred
+    };
+
+    monitors
+        .iter()
+        .find(|monitor| matches(monitor.connector(), &preference.connector))
+        .or_else(|| {
+            monitors
+                .iter()
+                .find(|monitor| matches(monitor.model(), &preference.model))
+        })
+        .cloned()
+}
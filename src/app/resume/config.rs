@@ -0,0 +1,9 @@
+pub const RESUME_FILE: &str = "watch-later.json";
+pub const RESUME_EXPIRY_DAYS: i64 = 30;
+/// Positions closer to the start are not worth offering
+pub const MIN_RESUME_POSITION: f64 = 30.0;
+/// Portion of the duration after which the media is considered watched
+pub const WATCHED_RATIO: f64 = 0.95;
+/// Streams from the streaming server are resumed by the web UI itself
+pub const STREAMING_SERVER_HOSTS: &[&str] = &["127.0.0.1", "localhost"];
+pub const STREAMING_SERVER_PORT: u16 = 11470;
@@ -0,0 +1,150 @@
+mod config;
+
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use anyhow::Context;
+use chrono::{Duration, Utc};
+use gtk::glib::{self, ChecksumType};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use url::Url;
+
+use crate::{
+    app::resume::config::{
+        MIN_RESUME_POSITION, RESUME_EXPIRY_DAYS, RESUME_FILE, STREAMING_SERVER_HOSTS,
+        STREAMING_SERVER_PORT, WATCHED_RATIO,
+    },
+    config::DATA_DIR,
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct ResumeEntry {
+    position: f64,
+    updated: i64,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(transparent)]
+pub struct ResumeStore {
+    entries: HashMap<String, ResumeEntry>,
+}
+
+impl ResumeStore {
+    pub fn load() -> Self {
+        let Some(file) = resume_file() else {
+            return Self::default();
+        };
+
+        let mut store: Self = fs::read_to_string(&file)
+            .ok()
+            .and_then(|data| {
+                serde_json::from_str(&data)
+                    .map_err(|e| error!("Failed to parse resume positions: {e}"))
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        let expiry = (Utc::now() - Duration::days(RESUME_EXPIRY_DAYS)).timestamp();
+        store.entries.retain(|_, entry| entry.updated > expiry);
+
+        store
+    }
+
+    pub fn get(&self, media: &str) -> Option<f64> {
+        self.entries
+            .get(&media_key(media))
+            .map(|entry| entry.position)
+    }
+
+    /// Forgets the media once it was watched until the end
+    pub fn record(&mut self, media: &str, position: f64, duration: f64) {
+        let key = media_key(media);
+        let watched = duration > 0.0 && position >= duration * WATCHED_RATIO;
+
+        match position < MIN_RESUME_POSITION || watched {
+            true => {
+                if self.entries.remove(&key).is_none() {
+                    return;
+                }
+            }
+            false => {
+                let entry = ResumeEntry {
+                    position,
+                    updated: Utc::now().timestamp(),
+                };
+
+                self.entries.insert(key, entry);
+            }
+        }
+
+        if let Err(e) = self.write() {
+            error!("Failed to save resume positions: {e}");
+        }
+    }
+
+    fn write(&self) -> anyhow::Result<()> {
+        let file = resume_file().context("Failed to get data dir")?;
+
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent).context("Failed to create data directory")?;
+        }
+
+        let data = serde_json::to_string(self).context("Failed to serialize resume positions")?;
+        fs::write(&file, data)
+            .with_context(|| format!("Failed to write resume positions to {}", file.display()))
+    }
+}
+
+pub fn format_position(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Local files are always resumed by the shell, urls only when the shell opened them
+/// and they are not served by the streaming server
+pub fn is_resumable(media: &str, opened_by_shell: bool) -> bool {
+    match Url::parse(media) {
+        Ok(url) if url.scheme() == "file" => true,
+        Ok(url) => {
+            let streaming_server = url
+                .host_str()
+                .is_some_and(|host| STREAMING_SERVER_HOSTS.contains(&host))
+                && url.port() == Some(STREAMING_SERVER_PORT);
+
+            opened_by_shell && !streaming_server
+        }
+        Err(_) => true,
+    }
+}
+
+/// Local files are keyed by their canonical path and urls by their normalised form,
+/// hashed so that tokens in urls are not stored as is
+fn media_key(media: &str) -> String {
+    let normalized = match Url::parse(media) {
+        Ok(url) if url.scheme() == "file" => url
+            .to_file_path()
+            .ok()
+            .and_then(|path| path.canonicalize().ok())
+            .map(|path| path.to_string_lossy().into_owned()),
+        Ok(url) => Some(url.to_string()),
+        Err(_) => PathBuf::from(media)
+            .canonicalize()
+            .ok()
+            .map(|path| path.to_string_lossy().into_owned()),
+    }
+    .unwrap_or(media.to_owned());
+
+    glib::compute_checksum_for_string(ChecksumType::Sha256, &normalized)
+        .map(String::from)
+        .unwrap_or(normalized)
+}
+
+fn resume_file() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(DATA_DIR).join(RESUME_FILE))
+}
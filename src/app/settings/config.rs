@@ -0,0 +1 @@
+pub const SETTINGS_FILE: &str = "settings.json";
@@ -0,0 +1,162 @@
+mod config;
+
+use std::{collections::HashMap, fs, path::PathBuf, str::FromStr};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{
+    app::{config::DEFAULT_SKIP_PATTERNS, settings::config::SETTINGS_FILE},
+    config::DATA_DIR,
+};
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CloseAction {
+    #[default]
+    Tray,
+    Minimize,
+    Quit,
+}
+
+impl CloseAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CloseAction::Tray => "tray",
+            CloseAction::Minimize => "minimize",
+            CloseAction::Quit => "quit",
+        }
+    }
+}
+
+impl FromStr for CloseAction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "tray" => Ok(CloseAction::Tray),
+            "minimize" => Ok(CloseAction::Minimize),
+            "quit" => Ok(CloseAction::Quit),
+            value => Err(format!("Unknown close action {value}")),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HiddenPlayback {
+    Pause,
+    #[default]
+    AudioOnly,
+    KeepPlaying,
+}
+
+impl HiddenPlayback {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HiddenPlayback::Pause => "pause",
+            HiddenPlayback::AudioOnly => "audio-only",
+            HiddenPlayback::KeepPlaying => "keep-playing",
+        }
+    }
+}
+
+impl FromStr for HiddenPlayback {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "pause" => Ok(HiddenPlayback::Pause),
+            "audio-only" => Ok(HiddenPlayback::AudioOnly),
+            "keep-playing" => Ok(HiddenPlayback::KeepPlaying),
+            value => Err(format!("Unknown hidden playback mode {value}")),
+        }
+    }
+}
+
+/// Monitor matched by connector first, by model when outputs were renumbered
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct MonitorPreference {
+    pub connector: Option<String>,
+    pub model: Option<String>,
+}
+
+/// Video adjustments kept for every episode of a show
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ShowOverride {
+    pub aspect: Option<String>,
+    pub zoom: f64,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Settings {
+    pub close_action: CloseAction,
+    pub hidden_playback: HiddenPlayback,
+    pub autostart: bool,
+    pub ask_download_location: bool,
+    pub audio_device: Option<String>,
+    pub audio_spdif: String,
+    pub audio_channels: Option<String>,
+    pub audio_exclusive: bool,
+    pub night_mode: bool,
+    pub skip_patterns: Option<Vec<String>>,
+    pub auto_skip: bool,
+    pub show_overrides: HashMap<String, ShowOverride>,
+    pub auto_crop: bool,
+    pub fit_window: bool,
+    pub lock_aspect: bool,
+    pub fullscreen_monitor: Option<MonitorPreference>,
+    pub shader_preset: Option<String>,
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let Some(file) = settings_file() else {
+            return Self::default();
+        };
+
+        fs::read_to_string(&file)
+            .ok()
+            .and_then(|data| {
+                serde_json::from_str(&data)
+                    .map_err(|e| error!("Failed to parse settings: {e}"))
+                    .ok()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn skip_patterns(&self) -> Vec<String> {
+        self.skip_patterns.clone().unwrap_or_else(|| {
+            DEFAULT_SKIP_PATTERNS
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect()
+        })
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = self.write() {
+            error!("Failed to save settings: {e}");
+        }
+    }
+
+    fn write(&self) -> anyhow::Result<()> {
+        let file = settings_file().context("Failed to get config dir")?;
+
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+
+        let data = serde_json::to_string_pretty(self).context("Failed to serialize settings")?;
+        fs::write(&file, data)
+            .with_context(|| format!("Failed to write settings to {}", file.display()))
+    }
+}
+
+fn settings_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(DATA_DIR).join(SETTINGS_FILE))
+}
@@ -0,0 +1,18 @@
+pub const SHADERS_DIR: &str = "shaders";
+pub const SHADER_EXTENSION: &str = "glsl";
+pub const PRESETS_FILE: &str = "presets.json";
+
+/// Presets used when the shaders directory has no presets file,
+/// only kept if all of their shaders are installed
+pub const DEFAULT_PRESETS: &[(&str, &[&str])] = &[
+    (
+        "Anime Upscale",
+        &[
+            "Anime4K_Clamp_Highlights.glsl",
+            "Anime4K_Restore_CNN_M.glsl",
+            "Anime4K_Upscale_CNN_x2_M.glsl",
+        ],
+    ),
+    ("FSR Upscale", &["FSR.glsl"]),
+    ("Sharpen", &["adaptive-sharpen.glsl"]),
+];
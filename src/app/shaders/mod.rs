@@ -0,0 +1,100 @@
+mod config;
+
+use std::{fs, path::PathBuf};
+
+use itertools::Itertools;
+use serde::Deserialize;
+use tracing::{error, warn};
+
+use crate::{
+    app::shaders::config::{DEFAULT_PRESETS, PRESETS_FILE, SHADER_EXTENSION, SHADERS_DIR},
+    config::DATA_DIR,
+};
+
+#[derive(Deserialize, Debug)]
+struct PresetDefinition {
+    name: String,
+    shaders: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ShaderPreset {
+    pub name: String,
+    pub shaders: Vec<PathBuf>,
+}
+
+/// Presets from the presets file of the shaders directory,
+/// followed by a preset for every shader that is not part of one
+pub fn load_presets() -> Vec<ShaderPreset> {
+    let Some(dir) = dirs::config_dir().map(|dir| dir.join(DATA_DIR).join(SHADERS_DIR)) else {
+        return vec![];
+    };
+
+    let files = fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == SHADER_EXTENSION))
+                .sorted()
+                .collect_vec()
+        })
+        .unwrap_or_default();
+
+    let definitions = fs::read_to_string(dir.join(PRESETS_FILE))
+        .ok()
+        .and_then(|data| {
+            serde_json::from_str::<Vec<PresetDefinition>>(&data)
+                .map_err(|e| error!("Failed to parse shader presets: {e}"))
+                .ok()
+        })
+        .unwrap_or_else(|| {
+            DEFAULT_PRESETS
+                .iter()
+                .map(|(name, shaders)| PresetDefinition {
+                    name: name.to_string(),
+                    shaders: shaders.iter().map(|shader| shader.to_string()).collect(),
+                })
+                .collect()
+        });
+
+    let mut presets = definitions
+        .into_iter()
+        .filter_map(|definition| {
+            let shaders = definition
+                .shaders
+                .iter()
+                .map(|shader| dir.join(shader))
+                .collect_vec();
+
+            match shaders.iter().find(|shader| !files.contains(shader)) {
+                Some(missing) => {
+                    warn!(
+                        "Skipping shader preset {}: {} not found",
+                        definition.name,
+                        missing.display()
+                    );
+                    None
+                }
+                None => Some(ShaderPreset {
+                    name: definition.name,
+                    shaders,
+                }),
+            }
+        })
+        .collect_vec();
+
+    let unused = files
+        .iter()
+        .filter(|file| !presets.iter().any(|preset| preset.shaders.contains(file)))
+        .filter_map(|file| {
+            let name = file.file_stem()?.to_string_lossy().into_owned();
+            Some(ShaderPreset {
+                name,
+                shaders: vec![file.to_owned()],
+            })
+        })
+        .collect_vec();
+
+    presets.extend(unused);
+    presets
+}
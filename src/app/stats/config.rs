@@ -0,0 +1 @@
+pub const SAMPLE_INTERVAL: u64 = 1000;
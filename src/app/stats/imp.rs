@@ -0,0 +1,176 @@
+use std::{cell::RefCell, time::Duration};
+
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::{
+    glib::{self, SourceId, WeakRef, clone},
+    prelude::*,
+};
+use itertools::Itertools;
+
+use crate::app::{
+    stats::config::SAMPLE_INTERVAL,
+    video::{Video, VideoStats},
+};
+
+#[derive(Default)]
+pub struct Stats {
+    pub video: WeakRef<Video>,
+    label: gtk::Label,
+    copy_button: gtk::Button,
+    timer: RefCell<Option<SourceId>>,
+}
+
+impl Stats {
+    fn sample(&self) {
+        if let Some(video) = self.video.upgrade() {
+            self.label.set_text(&format_stats(&video.stats()));
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for Stats {
+    const NAME: &'static str = "Stats";
+    type Type = super::Stats;
+    type ParentType = gtk::Box;
+}
+
+impl ObjectImpl for Stats {
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let object = self.obj();
+        object.set_orientation(gtk::Orientation::Vertical);
+        object.set_spacing(6);
+        object.set_halign(gtk::Align::Start);
+        object.set_valign(gtk::Align::Start);
+        object.set_margin_top(12);
+        object.set_margin_start(12);
+        object.add_css_class("osd");
+        object.add_css_class("toolbar");
+
+        self.label.set_xalign(0.0);
+        self.label.add_css_class("monospace");
+
+        self.copy_button.set_label(&gettext("Copy Stats"));
+        self.copy_button.set_halign(gtk::Align::End);
+        self.copy_button.connect_clicked(clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |button| {
+                button.clipboard().set_text(&imp.label.text());
+            }
+        ));
+
+        object.append(&self.label);
+        object.append(&self.copy_button);
+    }
+}
+
+impl WidgetImpl for Stats {
+    fn map(&self) {
+        self.parent_map();
+
+        self.sample();
+
+        let timer = glib::timeout_add_local(
+            Duration::from_millis(SAMPLE_INTERVAL),
+            clone!(
+                #[weak(rename_to = imp)]
+                self,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || {
+                    imp.sample();
+                    glib::ControlFlow::Continue
+                }
+            ),
+        );
+
+        *self.timer.borrow_mut() = Some(timer);
+    }
+
+    fn unmap(&self) {
+        if let Some(timer) = self.timer.take() {
+            timer.remove();
+        }
+
+        self.parent_unmap();
+    }
+}
+
+impl BoxImpl for Stats {}
+
+fn format_stats(stats: &VideoStats) -> String {
+    let unknown = || "-".to_owned();
+
+    let video = match (&stats.video_codec, stats.width, stats.height) {
+        (Some(codec), Some(width), Some(height)) => {
+            let fps = stats
+                .fps
+                .map(|fps| format!(" {fps:.3} fps"))
+                .unwrap_or_default();
+            format!("{codec} {width}x{height}{fps}")
+        }
+        _ => unknown(),
+    };
+
+    let lines = [
+        (gettext("Video"), video),
+        (
+            gettext("Audio"),
+            stats.audio_codec.clone().unwrap_or_else(unknown),
+        ),
+        (
+            gettext("Hardware decoding"),
+            stats.hwdec.clone().unwrap_or_else(|| gettext("no")),
+        ),
+        (
+            gettext("Dropped frames"),
+            format!(
+                "{} / {} {}",
+                stats.dropped_frames.unwrap_or_default(),
+                stats.decoder_dropped_frames.unwrap_or_default(),
+                gettext("decoder"),
+            ),
+        ),
+        (
+            gettext("Delayed frames"),
+            stats.delayed_frames.unwrap_or_default().to_string(),
+        ),
+        (
+            gettext("A/V sync"),
+            stats
+                .avsync
+                .map(|avsync| format!("{avsync:+.3} s"))
+                .unwrap_or_else(unknown),
+        ),
+        (
+            gettext("Cache"),
+            stats
+                .cache_duration
+                .map(|duration| format!("{duration:.1} s"))
+                .unwrap_or_else(unknown),
+        ),
+        (
+            gettext("Download rate"),
+            stats.download_rate.map(format_rate).unwrap_or_else(unknown),
+        ),
+    ];
+
+    lines
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}"))
+        .join("\n")
+}
+
+fn format_rate(bytes: i64) -> String {
+    let bytes = bytes as f64;
+
+    match bytes {
+        bytes if bytes >= 1024.0 * 1024.0 => format!("{:.1} MiB/s", bytes / 1024.0 / 1024.0),
+        bytes if bytes >= 1024.0 => format!("{:.1} KiB/s", bytes / 1024.0),
+        bytes => format!("{bytes} B/s"),
+    }
+}
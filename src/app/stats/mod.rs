@@ -0,0 +1,21 @@
+mod config;
+mod imp;
+
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use crate::app::video::Video;
+
+glib::wrapper! {
+    pub struct Stats(ObjectSubclass<imp::Stats>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl Stats {
+    pub fn new(video: &Video) -> Self {
+        let stats: Self = glib::Object::builder().property("visible", false).build();
+        stats.imp().video.set(Some(video));
+        stats
+    }
+}
@@ -2,3 +2,8 @@ pub const ICON_FILE: &[u8] = include_bytes!(concat!(
     env!("CARGO_MANIFEST_DIR"),
     "/data/icons/symbolic.png"
 ));
+
+pub const STATUS_NOTIFIER_WATCHER: &str = "org.kde.StatusNotifierWatcher";
+
+pub const SEEK_OFFSET: f64 = 10.0;
+pub const VOLUME_STEP: f64 = 10.0;
@@ -1,39 +1,154 @@
-use std::sync::{
-    Arc, LazyLock, OnceLock,
-    mpsc::{Sender, channel},
+use std::{
+    cell::{Cell, RefCell},
+    sync::{Arc, LazyLock, OnceLock},
 };
 
 use gettextrs::gettext;
 use gtk::{
-    glib::{self, object::ObjectExt, subclass::Signal},
+    gio,
+    glib::{self, Properties, object::ObjectExt, subclass::Signal, types::StaticType},
     subclass::prelude::*,
 };
-use ksni::{Handle, MenuItem, TrayMethods, menu::StandardItem};
-use tokio::sync::Mutex;
+use ksni::{
+    Handle, MenuItem, OfflineReason, ToolTip, TrayMethods,
+    menu::{CheckmarkItem, StandardItem, SubMenu},
+};
+use tokio::sync::{
+    Mutex,
+    mpsc::{UnboundedSender, unbounded_channel},
+};
+use tracing::{error, warn};
 
 use crate::{
     app::{
         config::{APP_ID, APP_NAME},
-        tray::config::ICON_FILE,
+        tray::config::{ICON_FILE, SEEK_OFFSET, STATUS_NOTIFIER_WATCHER, VOLUME_STEP},
     },
     spawn_local,
 };
 
-#[derive(Default)]
+#[derive(Properties, Default)]
+#[properties(wrapper_type = super::Tray)]
 pub struct Tray {
     handle: Arc<Mutex<Option<Handle<TrayIcon>>>>,
+    sender: OnceLock<UnboundedSender<TrayEvent>>,
+    watcher: RefCell<Option<gio::WatcherId>>,
+    /// Last state given to the icon, used to seed it again when respawned
+    window_hidden: Cell<bool>,
+    playback: RefCell<Option<TrayPlayback>>,
+    #[property(get)]
+    available: Cell<bool>,
+    /// Whether the first attempt to spawn the icon finished
+    #[property(get)]
+    ready: Cell<bool>,
 }
 
 impl Tray {
-    pub fn update(&self, state: bool) {
+    fn update<F: FnOnce(&mut TrayIcon) + Send + 'static>(&self, f: F) {
         let local_handle = self.handle.clone();
         spawn_local!(async move {
             let handle_guard = local_handle.lock().await;
             if let Some(handle) = handle_guard.as_ref() {
-                handle.update(|tray| tray.window_visible = state).await;
+                handle.update(f).await;
             }
         });
     }
+
+    pub fn update_window_visible(&self, state: bool) {
+        self.window_hidden.set(!state);
+        self.update(move |tray| tray.window_visible = state);
+    }
+
+    pub fn update_playback(&self, playback: Option<TrayPlayback>) {
+        self.playback.replace(playback.clone());
+        self.update(move |tray| tray.playback = playback);
+    }
+
+    fn set_available(&self, available: bool) {
+        if self.available.replace(available) != available {
+            self.obj().notify_available();
+        }
+    }
+
+    fn spawn_icon(&self) {
+        let Some(sender) = self.sender.get().cloned() else {
+            return;
+        };
+
+        let tray_icon = TrayIcon {
+            sender,
+            window_visible: !self.window_hidden.get(),
+            playback: self.playback.borrow().clone(),
+        };
+
+        let local_handle = self.handle.clone();
+        let object_weak = self.obj().downgrade();
+        spawn_local!(async move {
+            let mut handle_guard = local_handle.lock().await;
+            if handle_guard.is_some() {
+                return;
+            }
+
+            let result = tray_icon.spawn_without_dbus_name().await;
+            let Some(object) = object_weak.upgrade() else {
+                return;
+            };
+
+            match result {
+                Ok(handle) => {
+                    *handle_guard = Some(handle);
+                    object.imp().set_spawned(true);
+                }
+                Err(e) => {
+                    error!("Failed to create tray icon: {e}");
+                    object.imp().set_spawned(false);
+                }
+            }
+        });
+    }
+
+    /// ksni only reports a host coming online after it went offline,
+    /// so a successful spawn is what makes the tray available
+    fn set_spawned(&self, spawned: bool) {
+        self.set_available(spawned);
+
+        if !self.ready.replace(true) {
+            self.obj().notify_ready();
+        }
+
+        match spawned {
+            true => self.unwatch_host(),
+            false => self.watch_host(),
+        }
+    }
+
+    /// ksni gives up when no host is running, so spawn again once one appears
+    fn watch_host(&self) {
+        if self.watcher.borrow().is_some() {
+            return;
+        }
+
+        let object_weak = self.obj().downgrade();
+        let watcher = gio::bus_watch_name(
+            gio::BusType::Session,
+            STATUS_NOTIFIER_WATCHER,
+            gio::BusNameWatcherFlags::NONE,
+            move |_, _, _| {
+                if let Some(object) = object_weak.upgrade() {
+                    object.imp().spawn_icon();
+                }
+            },
+            |_, _| {},
+        );
+
+        self.watcher.replace(Some(watcher));
+    }
+
+    fn unwatch_host(&self) {
+        if let Some(watcher) = self.watcher.take() {
+            gio::bus_unwatch_name(watcher);
+        }
+    }
 }
 
 #[glib::object_subclass]
@@ -43,6 +158,7 @@ impl ObjectSubclass for Tray {
     type ParentType = glib::Object;
 }
 
+#[glib::derived_properties]
 impl ObjectImpl for Tray {
     fn signals() -> &'static [Signal] {
         static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
@@ -50,7 +166,17 @@ impl ObjectImpl for Tray {
             vec![
                 Signal::builder("show").build(),
                 Signal::builder("hide").build(),
+                Signal::builder("toggle").build(),
                 Signal::builder("quit").build(),
+                Signal::builder("play-pause").build(),
+                Signal::builder("stop").build(),
+                Signal::builder("seek")
+                    .param_types([f64::static_type()])
+                    .build(),
+                Signal::builder("toggle-mute").build(),
+                Signal::builder("volume")
+                    .param_types([f64::static_type()])
+                    .build(),
             ]
         })
     }
@@ -58,50 +184,130 @@ impl ObjectImpl for Tray {
     fn constructed(&self) {
         self.parent_constructed();
 
-        let (sender, receiver) = channel::<TrayEvent>();
-
-        let tray_icon = TrayIcon {
-            sender,
-            window_visible: true,
-        };
+        let (sender, mut receiver) = unbounded_channel::<TrayEvent>();
+        self.sender.set(sender).ok();
+        self.spawn_icon();
 
-        let local_handle = self.handle.clone();
+        let object_weak = self.obj().downgrade();
         spawn_local!(async move {
-            let mut handle_guard = local_handle.lock().await;
-            let handle = tray_icon
-                .spawn_without_dbus_name()
-                .await
-                .expect("Failed to create tray icon");
-            *handle_guard = Some(handle);
-        });
+            while let Some(event) = receiver.recv().await {
+                let Some(object) = object_weak.upgrade() else {
+                    break;
+                };
 
-        let object_weak = self.obj().downgrade();
-        glib::idle_add_local(move || {
-            receiver.try_iter().for_each(|event| {
-                if let Some(object) = object_weak.upgrade() {
-                    match event {
-                        TrayEvent::Show => object.emit_by_name::<()>("show", &[]),
-                        TrayEvent::Hide => object.emit_by_name::<()>("hide", &[]),
-                        TrayEvent::Quit => object.emit_by_name::<()>("quit", &[]),
-                    }
+                match event {
+                    TrayEvent::Available(state) => object.imp().set_available(state),
+                    TrayEvent::Show => object.emit_by_name::<()>("show", &[]),
+                    TrayEvent::Hide => object.emit_by_name::<()>("hide", &[]),
+                    TrayEvent::Toggle => object.emit_by_name::<()>("toggle", &[]),
+                    TrayEvent::Quit => object.emit_by_name::<()>("quit", &[]),
+                    TrayEvent::PlayPause => object.emit_by_name::<()>("play-pause", &[]),
+                    TrayEvent::Stop => object.emit_by_name::<()>("stop", &[]),
+                    TrayEvent::Seek(offset) => object.emit_by_name::<()>("seek", &[&offset]),
+                    TrayEvent::ToggleMute => object.emit_by_name::<()>("toggle-mute", &[]),
+                    TrayEvent::Volume(volume) => object.emit_by_name::<()>("volume", &[&volume]),
                 }
-            });
-
-            glib::ControlFlow::Continue
+            }
         });
     }
+
+    fn dispose(&self) {
+        self.unwatch_host();
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum TrayEvent {
+    Available(bool),
     Show,
     Hide,
+    Toggle,
     Quit,
+    PlayPause,
+    Stop,
+    Seek(f64),
+    ToggleMute,
+    Volume(f64),
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct TrayPlayback {
+    pub title: Option<String>,
+    pub paused: bool,
+    pub muted: bool,
+    pub volume: f64,
 }
 
 pub struct TrayIcon {
-    sender: Sender<TrayEvent>,
-    window_visible: bool,
+    sender: UnboundedSender<TrayEvent>,
+    pub window_visible: bool,
+    pub playback: Option<TrayPlayback>,
+}
+
+impl TrayIcon {
+    fn item(&self, label: String, event: TrayEvent) -> StandardItem<Self> {
+        let sender = self.sender.clone();
+
+        StandardItem {
+            label,
+            activate: Box::new(move |_| {
+                sender.send(event.clone()).ok();
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn playback_menu(&self, playback: &TrayPlayback) -> Vec<MenuItem<Self>> {
+        let play_pause_label = match playback.paused {
+            true => gettext("Play"),
+            false => gettext("Pause"),
+        };
+
+        let volume = playback.volume;
+        let sender_mute = self.sender.clone();
+
+        vec![
+            self.item(play_pause_label, TrayEvent::PlayPause).into(),
+            self.item(gettext("Stop"), TrayEvent::Stop).into(),
+            self.item(gettext("Seek backward"), TrayEvent::Seek(-SEEK_OFFSET))
+                .into(),
+            self.item(gettext("Seek forward"), TrayEvent::Seek(SEEK_OFFSET))
+                .into(),
+            SubMenu {
+                label: format!("{} ({}%)", gettext("Volume"), volume.round()),
+                submenu: vec![
+                    CheckmarkItem {
+                        label: gettext("Mute"),
+                        checked: playback.muted,
+                        activate: Box::new(move |_| {
+                            sender_mute.send(TrayEvent::ToggleMute).ok();
+                        }),
+                        ..Default::default()
+                    }
+                    .into(),
+                    StandardItem {
+                        enabled: volume < 100.0,
+                        ..self.item(
+                            gettext("Volume up"),
+                            TrayEvent::Volume(volume + VOLUME_STEP),
+                        )
+                    }
+                    .into(),
+                    StandardItem {
+                        enabled: volume > 0.0,
+                        ..self.item(
+                            gettext("Volume down"),
+                            TrayEvent::Volume(volume - VOLUME_STEP),
+                        )
+                    }
+                    .into(),
+                ],
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+        ]
+    }
 }
 
 impl ksni::Tray for TrayIcon {
@@ -113,6 +319,41 @@ impl ksni::Tray for TrayIcon {
         APP_NAME.into()
     }
 
+    fn tool_tip(&self) -> ToolTip {
+        let description = match &self.playback {
+            Some(playback) => {
+                let title = playback.title.clone().unwrap_or_default();
+                match playback.paused {
+                    true => format!("{} {title}", gettext("Paused:")),
+                    false => format!("{} {title}", gettext("Playing:")),
+                }
+            }
+            None => String::new(),
+        };
+
+        ToolTip {
+            title: APP_NAME.into(),
+            description,
+            ..Default::default()
+        }
+    }
+
+    fn watcher_online(&self) {
+        self.sender.send(TrayEvent::Available(true)).ok();
+    }
+
+    fn watcher_offline(&self, reason: OfflineReason) -> bool {
+        warn!("Tray host is not available: {reason:?}");
+        self.sender.send(TrayEvent::Available(false)).ok();
+
+        // Keep the service alive so the icon shows up once a host appears
+        true
+    }
+
+    fn activate(&mut self, _x: i32, _y: i32) {
+        self.sender.send(TrayEvent::Toggle).ok();
+    }
+
     fn icon_pixmap(&self) -> Vec<ksni::Icon> {
         static ICON: LazyLock<ksni::Icon> = LazyLock::new(|| {
             let (data, width, height) = load_image(ICON_FILE);
@@ -132,7 +373,13 @@ impl ksni::Tray for TrayIcon {
         let sender_hide = self.sender.clone();
         let sender_quit = self.sender.clone();
 
-        vec![
+        let mut items = self
+            .playback
+            .as_ref()
+            .map(|playback| self.playback_menu(playback))
+            .unwrap_or_default();
+
+        items.extend([
             StandardItem {
                 label: gettext("Show"),
                 visible: !self.window_visible,
@@ -159,7 +406,9 @@ impl ksni::Tray for TrayIcon {
                 ..Default::default()
             }
             .into(),
-        ]
+        ]);
+
+        items
     }
 }
 
@@ -177,3 +426,24 @@ fn load_image(buffer: &[u8]) -> (Vec<u8>, u32, u32) {
 
     (data, width, height)
 }
+
+#[cfg(test)]
+mod tests {
+    use gtk::{glib, subclass::prelude::*};
+
+    use crate::app::tray::Tray;
+
+    #[test]
+    fn spawned_tray_is_available() {
+        let _guard = glib::MainContext::default()
+            .acquire()
+            .expect("Failed to acquire main context");
+
+        let tray = Tray::default();
+        assert!(!tray.available());
+
+        tray.imp().set_spawned(true);
+        assert!(tray.available());
+        assert!(tray.ready());
+    }
+}
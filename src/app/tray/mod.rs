@@ -4,6 +4,8 @@ mod imp;
 use adw::subclass::prelude::ObjectSubclassIsExt;
 use gtk::glib::{self, closure_local, object::ObjectExt};
 
+pub use imp::TrayPlayback;
+
 glib::wrapper! {
     pub struct Tray(ObjectSubclass<imp::Tray>);
 }
@@ -16,7 +18,11 @@ impl Default for Tray {
 
 impl Tray {
     pub fn update(&self, state: bool) {
-        self.imp().update(state);
+        self.imp().update_window_visible(state);
+    }
+
+    pub fn update_playback(&self, playback: Option<TrayPlayback>) {
+        self.imp().update_playback(playback);
     }
 
     pub fn connect_show<T: Fn() + 'static>(&self, callback: T) {
@@ -48,4 +54,64 @@ impl Tray {
             }),
         );
     }
+
+    pub fn connect_toggle<T: Fn() + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "toggle",
+            false,
+            closure_local!(move |_: Tray| {
+                callback();
+            }),
+        );
+    }
+
+    pub fn connect_play_pause<T: Fn() + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "play-pause",
+            false,
+            closure_local!(move |_: Tray| {
+                callback();
+            }),
+        );
+    }
+
+    pub fn connect_stop<T: Fn() + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "stop",
+            false,
+            closure_local!(move |_: Tray| {
+                callback();
+            }),
+        );
+    }
+
+    pub fn connect_seek<T: Fn(f64) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "seek",
+            false,
+            closure_local!(move |_: Tray, value: f64| {
+                callback(value);
+            }),
+        );
+    }
+
+    pub fn connect_toggle_mute<T: Fn() + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "toggle-mute",
+            false,
+            closure_local!(move |_: Tray| {
+                callback();
+            }),
+        );
+    }
+
+    pub fn connect_volume<T: Fn(f64) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "volume",
+            false,
+            closure_local!(move |_: Tray, value: f64| {
+                callback(value);
+            }),
+        );
+    }
 }
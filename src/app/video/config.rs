@@ -7,6 +7,14 @@ pub const FLOAT_PROPERTIES: &[&str] = &[
     "sub-scale",
     "sub-delay",
     "cache-buffering-state",
+    "chapter",
+    "video-zoom",
+    "video-pan-x",
+    "video-pan-y",
+    "brightness",
+    "contrast",
+    "saturation",
+    "gamma",
 ];
 
 pub const BOOL_PROPERTIES: &[&str] = &[
@@ -18,6 +26,7 @@ pub const BOOL_PROPERTIES: &[&str] = &[
     "input-vo-keyboard",
     "eof-reached",
     "paused-for-cache",
+    "audio-exclusive",
 ];
 
 pub const STRING_PROPERTIES: &[&str] = &[
@@ -35,4 +44,44 @@ pub const STRING_PROPERTIES: &[&str] = &[
     "mute",
     "metadata",
     "video-params",
+    "audio-device",
+    "audio-device-list",
+    "audio-spdif",
+    "audio-channels",
+    "chapter-list",
+    "video-aspect-override",
+    "video-crop",
+    "video-rotate",
+    "deinterlace",
 ];
+
+pub const ASPECT_RATIOS: &[&str] = &["-1", "16:9", "4:3", "2.35:1", "1.85:1"];
+pub const DEFAULT_ASPECT: &str = "-1";
+
+pub const CROP_DETECT_LABEL: &str = "@cropdetect";
+pub const CROP_DETECT_FILTER: &str = "@cropdetect:lavfi=[cropdetect=limit=24/255:round=2:reset=1]";
+pub const CROP_DETECT_METADATA: &str = "vf-metadata/cropdetect/lavfi.cropdetect";
+pub const CROP_DETECT_INTERVAL: u64 = 250;
+/// Ticks sampled before stopping, whether or not frames reported a crop
+pub const CROP_DETECT_SAMPLES: usize = 20;
+/// Portion of the ticks that must agree before a crop is applied
+pub const CROP_STABLE_RATIO: f64 = 0.6;
+
+/// Log prefix of the renderer, which compiles the shaders
+pub const SHADER_LOG_PREFIX: &str = "vo/";
+/// Lowercase parts of the renderer errors about shaders failing to load or compile
+pub const SHADER_ERRORS: &[&str] = &[
+    "shader compil",
+    "compile shader",
+    "user shader",
+    "shader file",
+];
+/// Seconds after the shaders change or a file starts during which errors are blamed on them
+pub const SHADER_ERROR_WINDOW: u64 = 10;
+
+pub const NIGHT_MODE_LABEL: &str = "@night-mode";
+pub const NIGHT_MODE_FILTER: &str = "@night-mode:lavfi=[loudnorm=I=-23:LRA=7:TP=-2]";
+
+/// Reply id for properties observed by the shell itself, as opposed to the web UI
+pub const SHELL_OBSERVER: u64 = 1;
+pub const WEB_OBSERVER: u64 = 0;
@@ -1,31 +1,112 @@
 use gtk::{
-    gdk::GLContext,
-    glib::{self, Propagation, Variant, subclass::Signal},
+    gdk::{self, GLContext},
+    glib::{self, Propagation, Properties, Variant, clone, subclass::Signal},
     prelude::*,
     subclass::prelude::*,
 };
+use itertools::Itertools;
 use libc::{LC_NUMERIC, setlocale};
 use libmpv2::{
-    Format, Mpv, SetData,
+    Format, GetData, Mpv, SetData,
     events::{Event, PropertyData},
     render::{OpenGLInitParams, RenderContext, RenderParam, RenderParamApiType},
 };
+use serde::Deserialize;
+use serde_json::{Map, Value};
 use std::{
     cell::{Cell, RefCell},
+    collections::HashMap,
     env,
     os::raw::c_void,
-    sync::{OnceLock, mpsc::channel},
+    path::Path,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc::unbounded_channel;
+use tracing::{error, warn};
+use url::Url;
+
+use crate::{
+    app::video::{
+        AudioDevice, PlaybackState, Renderer,
+        config::{
+            CROP_DETECT_FILTER, CROP_DETECT_INTERVAL, CROP_DETECT_LABEL, CROP_DETECT_METADATA,
+            CROP_DETECT_SAMPLES, CROP_STABLE_RATIO, NIGHT_MODE_FILTER, NIGHT_MODE_LABEL,
+            SHADER_ERROR_WINDOW, SHADER_ERRORS, SHADER_LOG_PREFIX, SHELL_OBSERVER, WEB_OBSERVER,
+        },
+        software::SoftwareRenderer,
+        wakeup::Wakeup,
+    },
+    spawn_local,
 };
-use tracing::error;
 
 fn get_proc_address(_context: &GLContext, name: &str) -> *mut c_void {
     epoxy::get_proc_addr(name) as _
 }
 
+#[derive(Properties)]
+#[properties(wrapper_type = super::Video)]
 pub struct Video {
+    /// Declared before mpv so that its render context is freed before mpv is destroyed
+    software_renderer: RefCell<Option<SoftwareRenderer>>,
     mpv: RefCell<Mpv>,
+    /// Declared after mpv so that it outlives mpv calling it
+    wakeup: RefCell<Option<Wakeup>>,
     render_context: RefCell<Option<RenderContext>>,
+    /// A GL context belongs to the surface of its window and cannot follow the area
+    /// to another one, so the render context is recreated and the track kept to select again
+    restore_track: RefCell<Option<String>>,
     fbo: Cell<u32>,
+    /// Size of the GLArea buffer in device pixels, as given on resize
+    buffer_size: Cell<(i32, i32)>,
+    frame_clock_handler: RefCell<Option<(gdk::FrameClock, glib::SignalHandlerId)>>,
+    swap_pending: Cell<bool>,
+    #[property(get)]
+    active: Cell<bool>,
+    #[property(get)]
+    paused: Cell<bool>,
+    #[property(get)]
+    muted: Cell<bool>,
+    #[property(get)]
+    volume: Cell<f64>,
+    #[property(get)]
+    title: RefCell<Option<String>>,
+    #[property(get, builder(PlaybackState::default()))]
+    playback_state: Cell<PlaybackState>,
+    buffering: Cell<bool>,
+    metadata_title: RefCell<Option<String>>,
+    #[property(get)]
+    path: RefCell<Option<String>>,
+    #[property(get)]
+    media_title: RefCell<Option<String>>,
+    pub position: Cell<f64>,
+    pub duration: Cell<f64>,
+    pub video_size: Cell<(i64, i64)>,
+    resize_pending: Cell<bool>,
+    pub audio_devices: RefCell<Vec<AudioDevice>>,
+    #[property(get)]
+    skippable_chapter: RefCell<Option<String>>,
+    chapters: RefCell<Vec<Chapter>>,
+    chapter: Cell<i64>,
+    pub skip_patterns: RefCell<Vec<String>>,
+    pub auto_crop: Cell<bool>,
+    crop_detection: RefCell<Option<glib::SourceId>>,
+    crop_filter: Cell<bool>,
+    crop_samples: RefCell<Vec<String>>,
+    detected_crops: RefCell<HashMap<String, String>>,
+    manual_crops: RefCell<HashMap<String, String>>,
+    shaders_active: Cell<bool>,
+    shader_errors_until: Cell<Option<Instant>>,
+    night_mode: Cell<bool>,
+    #[property(get, builder(Renderer::default()))]
+    renderer: Cell<Renderer>,
+    pub picture: RefCell<Option<gtk::Picture>>,
+    pub suspended_track: RefCell<Option<String>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Chapter {
+    title: Option<String>,
 }
 
 impl Default for Video {
@@ -52,10 +133,73 @@ impl Default for Video {
 
         mpv.disable_deprecated_events().ok();
 
+        // Needed to report shaders that fail to compile
+        if let Err(e) = mpv.request_log_messages("error") {
+            error!("Failed to request log messages: {e}");
+        }
+
+        for (name, format) in [
+            ("pause", Format::Flag),
+            ("paused-for-cache", Format::Flag),
+            ("mute", Format::Flag),
+            ("volume", Format::Double),
+            ("metadata", Format::String),
+            ("path", Format::String),
+            ("media-title", Format::String),
+            ("audio-device-list", Format::String),
+            ("chapter-list", Format::String),
+            ("chapter", Format::Int64),
+            ("time-pos", Format::Double),
+            ("duration", Format::Double),
+            ("video-params/dw", Format::Int64),
+            ("video-params/dh", Format::Int64),
+        ] {
+            if let Err(e) = mpv.observe_property(name, format, SHELL_OBSERVER) {
+                error!("Failed to observe property {name}: {e}");
+            }
+        }
+
         Self {
+            software_renderer: Default::default(),
             mpv: RefCell::new(mpv),
+            wakeup: Default::default(),
             render_context: Default::default(),
+            restore_track: Default::default(),
             fbo: Default::default(),
+            buffer_size: Default::default(),
+            frame_clock_handler: Default::default(),
+            swap_pending: Default::default(),
+            active: Default::default(),
+            paused: Default::default(),
+            muted: Default::default(),
+            volume: Cell::new(100.0),
+            title: Default::default(),
+            playback_state: Default::default(),
+            buffering: Default::default(),
+            metadata_title: Default::default(),
+            path: Default::default(),
+            media_title: Default::default(),
+            position: Default::default(),
+            duration: Default::default(),
+            video_size: Default::default(),
+            resize_pending: Default::default(),
+            audio_devices: Default::default(),
+            skippable_chapter: Default::default(),
+            chapters: Default::default(),
+            chapter: Cell::new(-1),
+            skip_patterns: Default::default(),
+            auto_crop: Default::default(),
+            crop_detection: Default::default(),
+            crop_filter: Default::default(),
+            crop_samples: Default::default(),
+            detected_crops: Default::default(),
+            manual_crops: Default::default(),
+            shaders_active: Default::default(),
+            shader_errors_until: Default::default(),
+            night_mode: Default::default(),
+            renderer: Default::default(),
+            picture: Default::default(),
+            suspended_track: Default::default(),
         }
     }
 }
@@ -78,13 +222,398 @@ impl Video {
         fbo as i32
     }
 
-    fn on_event<T: Fn(Event)>(&self, callback: T) {
-        if let Some(result) = self.mpv.borrow_mut().wait_event(0.0) {
+    /// Handles all the pending events, mpv only wakes up once for several of them
+    fn on_events<T: Fn(Event)>(&self, callback: T) {
+        let mut mpv = self.mpv.borrow_mut();
+
+        while let Some(result) = mpv.wait_event(0.0) {
             match result {
                 Ok(event) => callback(event),
                 Err(e) => error!("Failed to wait for event: {e}"),
             }
+        }
+    }
+
+    fn update_state(&self, name: &str, change: PropertyData) {
+        let object = self.obj();
+
+        match (name, change) {
+            ("pause", PropertyData::Flag(value)) => {
+                self.paused.set(value);
+                object.notify_paused();
+                self.update_playback_state();
+            }
+            ("paused-for-cache", PropertyData::Flag(value)) => {
+                self.buffering.set(value);
+                self.update_playback_state();
+            }
+            ("mute", PropertyData::Flag(value)) => {
+                self.muted.set(value);
+                object.notify_muted();
+            }
+            ("volume", PropertyData::Double(value)) => {
+                self.volume.set(value);
+                object.notify_volume();
+            }
+            ("metadata", PropertyData::Str(value)) => {
+                *self.metadata_title.borrow_mut() = metadata_title(value);
+                self.update_title();
+            }
+            ("path", PropertyData::Str(value)) => {
+                *self.path.borrow_mut() = Some(value.to_owned());
+                object.notify_path();
+                self.update_title();
+
+                // Deferred since mpv can not be used while it is handling an event
+                glib::idle_add_local_once(clone!(
+                    #[weak]
+                    object,
+                    move || {
+                        object.imp().start_crop_detection();
+                    }
+                ));
+            }
+            ("media-title", PropertyData::Str(value)) => {
+                *self.media_title.borrow_mut() = Some(value.to_owned());
+                object.notify_media_title();
+            }
+            ("time-pos", PropertyData::Double(value)) => {
+                self.position.set(value);
+            }
+            ("duration", PropertyData::Double(value)) => {
+                self.duration.set(value);
+            }
+            ("video-params/dw", PropertyData::Int64(value)) => {
+                let (_, height) = self.video_size.get();
+                self.video_size.set((value, height));
+                self.queue_video_resized();
+            }
+            ("video-params/dh", PropertyData::Int64(value)) => {
+                let (width, _) = self.video_size.get();
+                self.video_size.set((width, value));
+                self.queue_video_resized();
+            }
+            ("audio-device-list", PropertyData::Str(value)) => {
+                match serde_json::from_str::<Vec<AudioDevice>>(value) {
+                    Ok(devices) => {
+                        *self.audio_devices.borrow_mut() = devices;
+                        object.emit_by_name::<()>("audio-devices-changed", &[]);
+                    }
+                    Err(e) => error!("Failed to parse audio device list: {e}"),
+                }
+            }
+            ("chapter-list", PropertyData::Str(value)) => {
+                *self.chapters.borrow_mut() = serde_json::from_str(value)
+                    .map_err(|e| error!("Failed to parse chapter list: {e}"))
+                    .unwrap_or_default();
+                self.update_skippable_chapter();
+            }
+            ("chapter", PropertyData::Int64(value)) => {
+                self.chapter.set(value);
+                self.update_skippable_chapter();
+            }
+            _ => {}
+        }
+    }
+
+    fn update_title(&self) {
+        let title = self
+            .metadata_title
+            .borrow()
+            .clone()
+            .or_else(|| self.path.borrow().as_deref().and_then(path_title));
+
+        if *self.title.borrow() != title {
+            *self.title.borrow_mut() = title;
+            self.obj().notify_title();
+        }
+    }
+
+    fn update_playback_state(&self) {
+        let state = match (self.active.get(), self.paused.get(), self.buffering.get()) {
+            (false, _, _) => PlaybackState::Stopped,
+            (true, true, _) => PlaybackState::Paused,
+            (true, false, true) => PlaybackState::Buffering,
+            (true, false, false) => PlaybackState::Playing,
         };
+
+        if self.playback_state.replace(state) != state {
+            self.obj().notify_playback_state();
+        }
+    }
+
+    pub fn update_skippable_chapter(&self) {
+        let chapters = self.chapters.borrow();
+        let patterns = self.skip_patterns.borrow();
+
+        let title = usize::try_from(self.chapter.get())
+            .ok()
+            .and_then(|index| chapters.get(index))
+            .and_then(|chapter| chapter.title.clone())
+            .filter(|title| patterns.iter().any(|pattern| matches_words(title, pattern)));
+
+        if *self.skippable_chapter.borrow() != title {
+            *self.skippable_chapter.borrow_mut() = title;
+            self.obj().notify_skippable_chapter();
+        }
+    }
+
+    fn set_active(&self, active: bool) {
+        self.active.set(active);
+        self.obj().notify_active();
+        self.update_playback_state();
+
+        if !active {
+            if let Some(source) = self.crop_detection.take() {
+                source.remove();
+            }
+
+            *self.metadata_title.borrow_mut() = None;
+            *self.path.borrow_mut() = None;
+            self.obj().notify_path();
+            *self.media_title.borrow_mut() = None;
+            self.obj().notify_media_title();
+            self.position.set(0.0);
+            self.duration.set(0.0);
+            self.update_title();
+
+            self.video_size.set((0, 0));
+            self.queue_video_resized();
+        }
+    }
+
+    /// Emits once both dimensions are updated, since mpv reports them separately
+    fn queue_video_resized(&self) {
+        if self.resize_pending.replace(true) {
+            return;
+        }
+
+        let object = self.obj();
+
+        glib::idle_add_local_once(clone!(
+            #[weak]
+            object,
+            move || {
+                object.imp().resize_pending.set(false);
+                object.emit_by_name::<()>("video-resized", &[]);
+            }
+        ));
+    }
+
+    /// Uses the crop previously set or detected for the file if any,
+    /// otherwise samples frames for a few seconds
+    pub fn start_crop_detection(&self) {
+        self.stop_crop_detection();
+
+        let Some(path) = self.path.borrow().clone() else {
+            return;
+        };
+
+        let auto_crop = self.auto_crop.get();
+        let cached = self.manual_crops.borrow().get(&path).cloned().or_else(|| {
+            auto_crop
+                .then(|| self.detected_crops.borrow().get(&path).cloned())
+                .flatten()
+        });
+
+        self.set_property("video-crop", cached.as_deref().unwrap_or_default());
+
+        if cached.is_some() || !auto_crop {
+            return;
+        }
+
+        self.send_command("vf", &["add", CROP_DETECT_FILTER]);
+        self.crop_filter.set(true);
+        self.crop_samples.borrow_mut().clear();
+
+        let object = self.obj();
+        let mut ticks = 0;
+        let source = glib::timeout_add_local(
+            Duration::from_millis(CROP_DETECT_INTERVAL),
+            clone!(
+                #[weak]
+                object,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || {
+                    let video = object.imp();
+
+                    if let Some(crop) = video.crop_sample() {
+                        video.crop_samples.borrow_mut().push(crop);
+                    }
+
+                    // Files without video never report any metadata
+                    ticks += 1;
+                    if ticks < CROP_DETECT_SAMPLES {
+                        return glib::ControlFlow::Continue;
+                    }
+
+                    video.crop_detection.take();
+                    video.finish_crop_detection(&path);
+
+                    glib::ControlFlow::Break
+                }
+            ),
+        );
+
+        *self.crop_detection.borrow_mut() = Some(source);
+    }
+
+    pub fn stop_crop_detection(&self) {
+        if let Some(source) = self.crop_detection.take() {
+            source.remove();
+        }
+
+        if self.crop_filter.take() {
+            self.send_command("vf", &["remove", CROP_DETECT_LABEL]);
+        }
+    }
+
+    /// Adds or removes only the night mode filter, keeping the rest of the audio filters
+    pub fn set_night_mode(&self, state: bool) {
+        if self.night_mode.replace(state) == state {
+            return;
+        }
+
+        match state {
+            true => self.send_command("af", &["add", NIGHT_MODE_FILTER]),
+            false => self.send_command("af", &["remove", NIGHT_MODE_LABEL]),
+        }
+    }
+
+    pub fn set_shaders(&self, list: &str, active: bool) {
+        self.set_property("glsl-shaders", list);
+        self.shaders_active.set(active);
+        self.watch_shader_errors();
+    }
+
+    /// Shaders are compiled on the first frames after they change or a file starts,
+    /// renderer errors are only blamed on them during that time
+    fn watch_shader_errors(&self) {
+        let until = self
+            .shaders_active
+            .get()
+            .then(|| Instant::now() + Duration::from_secs(SHADER_ERROR_WINDOW));
+
+        self.shader_errors_until.set(until);
+    }
+
+    fn is_shader_error(&self, prefix: &str, text: &str) -> bool {
+        let watching = self
+            .shader_errors_until
+            .get()
+            .is_some_and(|until| Instant::now() < until);
+
+        let text = text.to_lowercase();
+        watching
+            && prefix.starts_with(SHADER_LOG_PREFIX)
+            && SHADER_ERRORS.iter().any(|error| text.contains(error))
+    }
+
+    /// Overrides the detected crop for the current file, an empty crop disables it
+    pub fn set_crop(&self, crop: &str) {
+        self.stop_crop_detection();
+        self.set_property("video-crop", crop);
+
+        if let Some(path) = self.path.borrow().clone() {
+            self.manual_crops.borrow_mut().insert(path, crop.to_owned());
+        }
+    }
+
+    pub fn forget_crop(&self) {
+        if let Some(ref path) = *self.path.borrow() {
+            self.manual_crops.borrow_mut().remove(path);
+            self.detected_crops.borrow_mut().remove(path);
+        }
+    }
+
+    fn crop_sample(&self) -> Option<String> {
+        let value =
+            |key: &str| self.get_property::<String>(&format!("{CROP_DETECT_METADATA}.{key}"));
+
+        Some(format!(
+            "{}x{}+{}+{}",
+            value("w")?,
+            value("h")?,
+            value("x")?,
+            value("y")?
+        ))
+    }
+
+    /// Only applies the most frequent crop if enough of the ticks sampled agree on it
+    fn finish_crop_detection(&self, path: &str) {
+        self.stop_crop_detection();
+
+        let samples = self.crop_samples.take();
+        let stable = samples
+            .iter()
+            .counts()
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .filter(|(_, count)| *count as f64 >= CROP_DETECT_SAMPLES as f64 * CROP_STABLE_RATIO)
+            .map(|(crop, _)| crop.to_owned());
+
+        if let Some(crop) = stable {
+            self.set_property("video-crop", crop.as_str());
+            self.detected_crops
+                .borrow_mut()
+                .insert(path.to_owned(), crop);
+        }
+    }
+
+    pub fn use_software_rendering(&self) {
+        if self.software_renderer.borrow().is_some() {
+            return;
+        }
+
+        let (sender, mut receiver) = unbounded_channel::<()>();
+
+        let renderer = match SoftwareRenderer::new(&mut self.mpv.borrow_mut(), sender) {
+            Ok(renderer) => renderer,
+            Err(e) => {
+                error!("{e}");
+                return;
+            }
+        };
+
+        let picture = gtk::Picture::builder()
+            .content_fit(gtk::ContentFit::Fill)
+            .can_shrink(true)
+            .hexpand(true)
+            .vexpand(true)
+            .build();
+
+        let object = self.obj();
+        let object_weak = object.downgrade();
+        spawn_local!(async move {
+            while receiver.recv().await.is_some() {
+                let Some(object) = object_weak.upgrade() else {
+                    break;
+                };
+
+                object.imp().render_software();
+            }
+        });
+
+        *self.software_renderer.borrow_mut() = Some(renderer);
+        *self.picture.borrow_mut() = Some(picture);
+
+        self.renderer.set(Renderer::Software);
+        object.notify_renderer();
+    }
+
+    fn render_software(&self) {
+        if let Some(ref picture) = *self.picture.borrow()
+            && picture.is_mapped()
+            && let Some(ref mut renderer) = *self.software_renderer.borrow_mut()
+        {
+            let (width, height) = device_size(picture);
+            let texture = renderer.render(width, height);
+
+            if let Some(texture) = texture {
+                picture.set_paintable(Some(&texture));
+            }
+        }
     }
 
     pub fn send_command(&self, name: &str, args: &[&str]) {
@@ -94,16 +623,30 @@ impl Video {
     }
 
     pub fn observe_property(&self, name: &str, format: Format) {
-        if let Err(e) = self.mpv.borrow().observe_property(name, format, 0) {
+        if let Err(e) = self
+            .mpv
+            .borrow()
+            .observe_property(name, format, WEB_OBSERVER)
+        {
             error!("Failed to observe property {name}: {e}");
         }
     }
 
+    pub fn unobserve_properties(&self) {
+        if let Err(e) = self.mpv.borrow().unobserve_property(WEB_OBSERVER) {
+            error!("Failed to unobserve properties: {e}");
+        }
+    }
+
     pub fn set_property<T: SetData>(&self, name: &str, value: T) {
         if let Err(e) = self.mpv.borrow().set_property(name, value) {
             error!("Failed to set property {name}: {e}");
         }
     }
+
+    pub fn get_property<T: GetData>(&self, name: &str) -> Option<T> {
+        self.mpv.borrow().get_property(name).ok()
+    }
 }
 
 #[glib::object_subclass]
@@ -113,6 +656,7 @@ impl ObjectSubclass for Video {
     type ParentType = gtk::GLArea;
 }
 
+#[glib::derived_properties]
 impl ObjectImpl for Video {
     fn signals() -> &'static [Signal] {
         static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
@@ -123,6 +667,11 @@ impl ObjectImpl for Video {
                     .build(),
                 Signal::builder("playback-started").build(),
                 Signal::builder("playback-ended").build(),
+                Signal::builder("audio-devices-changed").build(),
+                Signal::builder("video-resized").build(),
+                Signal::builder("shader-error")
+                    .param_types([String::static_type()])
+                    .build(),
             ]
         })
     }
@@ -130,14 +679,27 @@ impl ObjectImpl for Video {
     fn constructed(&self) {
         self.parent_constructed();
 
+        let (wakeup, mut receiver) = Wakeup::new(&self.mpv.borrow());
+        *self.wakeup.borrow_mut() = Some(wakeup);
+
         let video_weak = self.downgrade();
         let object_weak = self.obj().downgrade();
 
-        glib::idle_add_local(move || {
-            if let Some(video) = video_weak.upgrade()
-                && let Some(object) = object_weak.upgrade()
-            {
-                video.on_event(|event| match event {
+        spawn_local!(async move {
+            while receiver.recv().await.is_some() {
+                let (Some(video), Some(object)) = (video_weak.upgrade(), object_weak.upgrade())
+                else {
+                    break;
+                };
+
+                video.on_events(|event| match event {
+                    Event::PropertyChange {
+                        name,
+                        change,
+                        reply_userdata: SHELL_OBSERVER,
+                    } => {
+                        video.update_state(name, change);
+                    }
                     Event::PropertyChange { name, change, .. } => {
                         let value = match change {
                             PropertyData::Str(v) => Some(v.to_variant()),
@@ -150,17 +712,27 @@ impl ObjectImpl for Video {
                             object.emit_by_name::<()>("property-changed", &[&name, &value]);
                         }
                     }
+                    Event::LogMessage { prefix, text, .. }
+                        if video.is_shader_error(prefix, text) =>
+                    {
+                        video.shaders_active.set(false);
+                        video.shader_errors_until.set(None);
+
+                        let message = format!("{prefix}: {}", text.trim());
+                        object.emit_by_name::<()>("shader-error", &[&message]);
+                    }
                     Event::StartFile => {
+                        video.watch_shader_errors();
+                        video.set_active(true);
                         object.emit_by_name::<()>("playback-started", &[]);
                     }
                     Event::EndFile(_) => {
                         object.emit_by_name::<()>("playback-ended", &[]);
+                        video.set_active(false);
                     }
                     _ => {}
                 });
             }
-
-            glib::ControlFlow::Continue
         });
     }
 }
@@ -169,10 +741,19 @@ impl WidgetImpl for Video {
     fn realize(&self) {
         self.parent_realize();
 
+        // The framebuffer of the previous realize is gone
+        self.fbo.set(0);
+
         let object = self.obj();
         object.make_current();
 
-        if object.error().is_some() {
+        if self.software_renderer.borrow().is_some() {
+            return;
+        }
+
+        if let Some(e) = object.error() {
+            warn!("OpenGL is unavailable, falling back to software rendering: {e}");
+            self.use_software_rendering();
             return;
         }
 
@@ -180,7 +761,7 @@ impl WidgetImpl for Video {
             let mut mpv = self.mpv.borrow_mut();
             let mpv_handle = unsafe { mpv.ctx.as_mut() };
 
-            let mut render_context = RenderContext::new(
+            let render_context = RenderContext::new(
                 mpv_handle,
                 vec![
                     RenderParam::ApiType(RenderParamApiType::OpenGl),
@@ -190,20 +771,32 @@ impl WidgetImpl for Video {
                     }),
                     RenderParam::BlockForTargetTime(false),
                 ],
-            )
-            .expect("Failed to create render context");
+            );
+
+            let mut render_context = match render_context {
+                Ok(render_context) => render_context,
+                Err(e) => {
+                    warn!(
+                        "Failed to create render context, falling back to software rendering: {e}"
+                    );
+                    drop(mpv);
+                    self.use_software_rendering();
+                    return;
+                }
+            };
+            drop(mpv);
 
-            let (sender, receiver) = channel::<()>();
+            let (sender, mut receiver) = unbounded_channel::<()>();
 
             let object_weak = object.downgrade();
-            glib::idle_add_local(move || {
-                if let Ok(()) = receiver.try_recv()
-                    && let Some(object) = object_weak.upgrade()
-                {
+            spawn_local!(async move {
+                while receiver.recv().await.is_some() {
+                    let Some(object) = object_weak.upgrade() else {
+                        break;
+                    };
+
                     object.queue_render();
                 }
-
-                glib::ControlFlow::Continue
             });
 
             render_context.set_update_callback(move || {
@@ -211,11 +804,46 @@ impl WidgetImpl for Video {
             });
 
             *self.render_context.borrow_mut() = Some(render_context);
+
+            if let Some(frame_clock) = object.frame_clock() {
+                let handler = frame_clock.connect_after_paint(clone!(
+                    #[weak(rename_to = video)]
+                    self,
+                    move |_| {
+                        if video.swap_pending.take()
+                            && let Some(ref render_context) = *video.render_context.borrow()
+                        {
+                            render_context.report_swap();
+                        }
+                    }
+                ));
+
+                *self.frame_clock_handler.borrow_mut() = Some((frame_clock, handler));
+            }
+
+            // Only selects the track again if mpv disabled it along with the old render context
+            if let Some(track) = self.restore_track.take()
+                && self.get_property::<String>("vid").as_deref() != Some(track.as_str())
+            {
+                self.set_property("vid", track.as_str());
+            }
         }
     }
 
     fn unrealize(&self) {
+        if let Some((frame_clock, handler)) = self.frame_clock_handler.take() {
+            frame_clock.disconnect(handler);
+        }
+
         if let Some(render_context) = self.render_context.borrow_mut().take() {
+            // Freeing the render context disables video, which happens when the area
+            // is moved to another window, so the track is kept to be restored on realize
+            if self.active.get() {
+                *self.restore_track.borrow_mut() = self
+                    .get_property::<String>("vid")
+                    .filter(|track| track != "no");
+            }
+
             drop(render_context);
         }
 
@@ -224,19 +852,84 @@ impl WidgetImpl for Video {
 }
 
 impl GLAreaImpl for Video {
-    fn render(&self, _context: &GLContext) -> Propagation {
-        let object = self.obj();
+    fn resize(&self, width: i32, height: i32) {
+        self.buffer_size.set((width, height));
+        self.parent_resize(width, height);
+    }
 
+    fn render(&self, _context: &GLContext) -> Propagation {
         let fbo = self.fbo();
-        let width = object.width();
-        let height = object.height();
+        let (width, height) = self.buffer_size.get();
 
         if let Some(ref render_context) = *self.render_context.borrow() {
-            render_context
-                .render::<GLContext>(fbo, width, height, true)
-                .expect("Failed to render");
+            match render_context.render::<GLContext>(fbo, width, height, true) {
+                Ok(()) => self.swap_pending.set(true),
+                Err(e) => {
+                    error!("Failed to render: {e}");
+
+                    // Query the framebuffer again on the next frame in case it changed
+                    self.fbo.set(0);
+                }
+            }
         }
 
         Propagation::Stop
     }
 }
+
+/// Whether all the words of the pattern appear in sequence in the text, ignoring case and punctuation
+fn matches_words(text: &str, pattern: &str) -> bool {
+    let words = |value: &str| {
+        value
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .join(" ")
+    };
+
+    let pattern = words(pattern);
+    !pattern.is_empty() && format!(" {} ", words(text)).contains(&format!(" {pattern} "))
+}
+
+/// Size in device pixels, taking fractional scales into account
+fn device_size(widget: &impl IsA<gtk::Widget>) -> (i32, i32) {
+    let scale = widget
+        .native()
+        .and_then(|native| native.surface())
+        .map(|surface| surface.scale())
+        .unwrap_or(widget.scale_factor() as f64);
+
+    (
+        (widget.width() as f64 * scale).round() as i32,
+        (widget.height() as f64 * scale).round() as i32,
+    )
+}
+
+fn metadata_title(metadata: &str) -> Option<String> {
+    let metadata = serde_json::from_str::<Map<String, Value>>(metadata).ok()?;
+
+    metadata
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("title"))
+        .and_then(|(_, value)| value.as_str())
+        .filter(|title| !title.is_empty())
+        .map(str::to_owned)
+}
+
+fn path_title(path: &str) -> Option<String> {
+    let name = match Url::parse(path) {
+        Ok(url) => url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .map(|segment| {
+                glib::Uri::unescape_string(segment, None)
+                    .map(String::from)
+                    .unwrap_or_else(|| segment.to_owned())
+            }),
+        Err(_) => Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+    };
+
+    name.filter(|name| !name.is_empty())
+}
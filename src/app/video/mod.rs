@@ -1,14 +1,73 @@
 mod config;
 mod imp;
+mod software;
+mod wakeup;
 
 use adw::subclass::prelude::ObjectSubclassIsExt;
-use gtk::glib::{self, Variant, closure_local, object::ObjectExt};
+use gtk::{
+    gdk,
+    glib::{self, Variant, closure_local, object::ObjectExt},
+    prelude::*,
+};
 use itertools::Itertools;
 use libmpv2::Format;
+use serde::Deserialize;
 use serde_json::{Number, Value};
+use std::{env, path::PathBuf};
 use tracing::error;
 
-use crate::app::video::config::{BOOL_PROPERTIES, FLOAT_PROPERTIES, STRING_PROPERTIES};
+use crate::app::video::config::{
+    ASPECT_RATIOS, BOOL_PROPERTIES, DEFAULT_ASPECT, FLOAT_PROPERTIES, STRING_PROPERTIES,
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "VideoPlaybackState")]
+pub enum PlaybackState {
+    #[default]
+    Stopped,
+    Playing,
+    Paused,
+    Buffering,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "VideoRenderer")]
+pub enum Renderer {
+    #[default]
+    OpenGl,
+    Software,
+}
+
+impl Renderer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Renderer::OpenGl => "opengl",
+            Renderer::Software => "software",
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct AudioDevice {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct VideoStats {
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    pub fps: Option<f64>,
+    pub hwdec: Option<String>,
+    pub dropped_frames: Option<i64>,
+    pub decoder_dropped_frames: Option<i64>,
+    pub delayed_frames: Option<i64>,
+    pub avsync: Option<f64>,
+    pub cache_duration: Option<f64>,
+    pub download_rate: Option<i64>,
+}
 
 glib::wrapper! {
     pub struct Video(ObjectSubclass<imp::Video>)
@@ -77,6 +136,260 @@ impl Video {
         );
     }
 
+    pub fn connect_audio_devices_changed<T: Fn(&Video) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "audio-devices-changed",
+            false,
+            closure_local!(move |video: Video| {
+                callback(&video);
+            }),
+        );
+    }
+
+    pub fn connect_video_resized<T: Fn(&Video) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "video-resized",
+            false,
+            closure_local!(move |video: Video| {
+                callback(&video);
+            }),
+        );
+    }
+
+    /// Display size of the video with its aspect ratio applied, none without a video track
+    pub fn video_size(&self) -> Option<(i32, i32)> {
+        let (width, height) = self.imp().video_size.get();
+
+        match (i32::try_from(width), i32::try_from(height)) {
+            (Ok(width), Ok(height)) if width > 0 && height > 0 => Some((width, height)),
+            _ => None,
+        }
+    }
+
+    /// Renders into a picture instead of the GL area, which has to be shown in its place
+    pub fn use_software_rendering(&self) {
+        self.imp().use_software_rendering();
+    }
+
+    pub fn picture(&self) -> Option<gtk::Picture> {
+        self.imp().picture.borrow().clone()
+    }
+
+    pub fn connect_shader_error<T: Fn(&str) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "shader-error",
+            false,
+            closure_local!(move |_: Video, message: &str| {
+                callback(message);
+            }),
+        );
+    }
+
+    pub fn audio_devices(&self) -> Vec<AudioDevice> {
+        self.imp().audio_devices.borrow().clone()
+    }
+
+    pub fn set_audio_device(&self, name: &str) {
+        self.imp().set_property("audio-device", name);
+    }
+
+    pub fn set_audio_passthrough(&self, codecs: &str) {
+        self.imp().set_property("audio-spdif", codecs);
+    }
+
+    pub fn set_audio_channels(&self, channels: &str) {
+        self.imp().set_property("audio-channels", channels);
+    }
+
+    pub fn set_audio_exclusive(&self, state: bool) {
+        self.imp().set_property("audio-exclusive", state);
+    }
+
+    pub fn set_night_mode(&self, state: bool) {
+        self.imp().set_night_mode(state);
+    }
+
+    pub fn stats(&self) -> VideoStats {
+        let widget = self.imp();
+
+        VideoStats {
+            video_codec: widget.get_property("video-codec"),
+            audio_codec: widget.get_property("audio-codec-name"),
+            width: widget.get_property("video-params/w"),
+            height: widget.get_property("video-params/h"),
+            fps: widget.get_property("estimated-vf-fps"),
+            hwdec: widget.get_property("hwdec-current"),
+            dropped_frames: widget.get_property("frame-drop-count"),
+            decoder_dropped_frames: widget.get_property("decoder-frame-drop-count"),
+            delayed_frames: widget.get_property("vo-delayed-frame-count"),
+            avsync: widget.get_property("avsync"),
+            cache_duration: widget.get_property("demuxer-cache-duration"),
+            download_rate: widget.get_property("cache-speed"),
+        }
+    }
+
+    pub fn set_skip_patterns(&self, patterns: Vec<String>) {
+        let widget = self.imp();
+
+        *widget.skip_patterns.borrow_mut() = patterns;
+        widget.update_skippable_chapter();
+    }
+
+    pub fn skip_chapter(&self) {
+        self.imp().send_command("add", &["chapter", "1"]);
+    }
+
+    /// Last known playback position, still available while playback is ending
+    pub fn position(&self) -> f64 {
+        self.imp().position.get()
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.imp().duration.get()
+    }
+
+    pub fn seek_to(&self, position: f64) {
+        self.imp()
+            .send_command("seek", &[&position.to_string(), "absolute"]);
+    }
+
+    pub fn adjust(&self, name: &str, delta: f64) {
+        self.imp().send_command("add", &[name, &delta.to_string()]);
+    }
+
+    pub fn video_zoom(&self) -> f64 {
+        self.imp().get_property("video-zoom").unwrap_or_default()
+    }
+
+    pub fn set_video_zoom(&self, zoom: f64) {
+        self.imp().set_property("video-zoom", zoom);
+    }
+
+    /// Aspect ratio override, none when the video's own aspect is used
+    pub fn video_aspect(&self) -> Option<String> {
+        self.imp()
+            .get_property::<String>("video-aspect-override")
+            .filter(|aspect| !aspect.parse::<f64>().is_ok_and(|value| value <= 0.0))
+    }
+
+    pub fn set_video_aspect(&self, aspect: Option<&str>) {
+        self.imp()
+            .set_property("video-aspect-override", aspect.unwrap_or(DEFAULT_ASPECT));
+    }
+
+    pub fn cycle_video_aspect(&self) {
+        let args = [&["video-aspect-override"], ASPECT_RATIOS].concat();
+        self.imp().send_command("cycle-values", &args);
+    }
+
+    pub fn set_shaders(&self, shaders: &[PathBuf]) {
+        let widget = self.imp();
+
+        let list = env::join_paths(shaders)
+            .map(|list| list.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        widget.set_shaders(&list, !shaders.is_empty());
+    }
+
+    pub fn set_auto_crop(&self, state: bool) {
+        let widget = self.imp();
+
+        widget.auto_crop.set(state);
+        widget.start_crop_detection();
+    }
+
+    /// Manual crop for the current file, an empty crop shows the whole frame
+    pub fn set_video_crop(&self, crop: &str) {
+        self.imp().set_crop(crop);
+    }
+
+    /// Drops the crop set or detected for the current file and detects it again
+    pub fn detect_crop(&self) {
+        let widget = self.imp();
+
+        widget.forget_crop();
+        widget.start_crop_detection();
+    }
+
+    pub fn rotate_video(&self) {
+        self.imp()
+            .send_command("cycle-values", &["video-rotate", "90", "180", "270", "0"]);
+    }
+
+    pub fn toggle_deinterlace(&self) {
+        self.imp().send_command("cycle", &["deinterlace"]);
+    }
+
+    pub fn reset_video(&self) {
+        let widget = self.imp();
+
+        for name in [
+            "video-zoom",
+            "video-pan-x",
+            "video-pan-y",
+            "brightness",
+            "contrast",
+            "saturation",
+            "gamma",
+        ] {
+            widget.set_property(name, 0.0);
+        }
+
+        widget.set_property("video-rotate", 0i64);
+        widget.set_property("video-aspect-override", DEFAULT_ASPECT);
+        widget.set_crop("");
+    }
+
+    /// Stops decoding video while keeping the audio playing
+    pub fn suspend_video(&self) {
+        let widget = self.imp();
+
+        if widget.suspended_track.borrow().is_some() {
+            return;
+        }
+
+        let track = widget
+            .get_property::<String>("vid")
+            .unwrap_or("auto".to_owned());
+        *widget.suspended_track.borrow_mut() = Some(track);
+
+        widget.set_property("vid", "no");
+    }
+
+    pub fn resume_video(&self) {
+        let widget = self.imp();
+
+        if let Some(track) = widget.suspended_track.take() {
+            widget.set_property("vid", track.as_str());
+        }
+    }
+
+    pub fn set_paused(&self, state: bool) {
+        self.imp().set_property("pause", state);
+    }
+
+    pub fn play_pause(&self) {
+        self.imp().send_command("cycle", &["pause"]);
+    }
+
+    pub fn stop(&self) {
+        self.imp().send_command("stop", &[]);
+    }
+
+    pub fn seek(&self, offset: f64) {
+        self.imp()
+            .send_command("seek", &[&offset.to_string(), "relative"]);
+    }
+
+    pub fn toggle_mute(&self) {
+        self.imp().send_command("cycle", &["mute"]);
+    }
+
+    pub fn set_volume(&self, volume: f64) {
+        self.imp().set_property("volume", volume.clamp(0.0, 100.0));
+    }
+
     pub fn send_command(&self, name: String, args: Vec<String>) {
         let widget = self.imp();
 
@@ -101,6 +414,11 @@ impl Video {
         };
     }
 
+    /// Drops the properties observed by the web UI, which observes them again once reloaded
+    pub fn reset_observers(&self) {
+        self.imp().unobserve_properties();
+    }
+
     pub fn set_property(&self, name: String, value: Value) {
         let widget = self.imp();
 
@@ -124,3 +442,10 @@ impl Video {
         };
     }
 }
+
+/// Whether an OpenGL context can be created on the default display
+pub fn gl_available() -> bool {
+    gdk::Display::default()
+        .and_then(|display| display.create_gl_context().ok())
+        .is_some_and(|context| context.realize().is_ok())
+}
@@ -0,0 +1,127 @@
+use std::{
+    ffi::{c_int, c_void},
+    ptr,
+};
+
+use anyhow::bail;
+use gtk::{gdk, glib};
+use libmpv2::Mpv;
+use libmpv2_sys::{
+    mpv_render_context, mpv_render_context_create, mpv_render_context_free,
+    mpv_render_context_render, mpv_render_context_set_update_callback, mpv_render_param,
+    mpv_render_param_type_MPV_RENDER_PARAM_API_TYPE as PARAM_API_TYPE,
+    mpv_render_param_type_MPV_RENDER_PARAM_INVALID as PARAM_INVALID,
+    mpv_render_param_type_MPV_RENDER_PARAM_SW_FORMAT as PARAM_SW_FORMAT,
+    mpv_render_param_type_MPV_RENDER_PARAM_SW_POINTER as PARAM_SW_POINTER,
+    mpv_render_param_type_MPV_RENDER_PARAM_SW_SIZE as PARAM_SW_SIZE,
+    mpv_render_param_type_MPV_RENDER_PARAM_SW_STRIDE as PARAM_SW_STRIDE,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+const BYTES_PER_PIXEL: usize = 4;
+
+/// Renders frames on the CPU through mpv's software render API,
+/// for systems without a usable OpenGL context
+pub struct SoftwareRenderer {
+    context: *mut mpv_render_context,
+    sender: *mut UnboundedSender<()>,
+    buffer: Vec<u8>,
+}
+
+impl SoftwareRenderer {
+    pub fn new(mpv: &mut Mpv, sender: UnboundedSender<()>) -> anyhow::Result<Self> {
+        let mut params = [
+            mpv_render_param {
+                type_: PARAM_API_TYPE,
+                data: c"sw".as_ptr() as *mut c_void,
+            },
+            mpv_render_param {
+                type_: PARAM_INVALID,
+                data: ptr::null_mut(),
+            },
+        ];
+
+        let mut context = ptr::null_mut();
+        let result = unsafe {
+            mpv_render_context_create(&mut context, mpv.ctx.as_ptr(), params.as_mut_ptr())
+        };
+
+        if result < 0 {
+            bail!("Failed to create software render context: {result}");
+        }
+
+        let sender = Box::into_raw(Box::new(sender));
+        unsafe {
+            mpv_render_context_set_update_callback(context, Some(on_update), sender as *mut c_void);
+        }
+
+        Ok(Self {
+            context,
+            sender,
+            buffer: vec![],
+        })
+    }
+
+    pub fn render(&mut self, width: i32, height: i32) -> Option<gdk::MemoryTexture> {
+        if width <= 0 || height <= 0 {
+            return None;
+        }
+
+        let stride = width as usize * BYTES_PER_PIXEL;
+        self.buffer.resize(stride * height as usize, 0);
+
+        let mut size: [c_int; 2] = [width, height];
+        let mut stride_param = stride;
+
+        let mut params = [
+            mpv_render_param {
+                type_: PARAM_SW_SIZE,
+                data: size.as_mut_ptr() as *mut c_void,
+            },
+            mpv_render_param {
+                type_: PARAM_SW_FORMAT,
+                data: c"rgb0".as_ptr() as *mut c_void,
+            },
+            mpv_render_param {
+                type_: PARAM_SW_STRIDE,
+                data: &mut stride_param as *mut usize as *mut c_void,
+            },
+            mpv_render_param {
+                type_: PARAM_SW_POINTER,
+                data: self.buffer.as_mut_ptr() as *mut c_void,
+            },
+            mpv_render_param {
+                type_: PARAM_INVALID,
+                data: ptr::null_mut(),
+            },
+        ];
+
+        let result = unsafe { mpv_render_context_render(self.context, params.as_mut_ptr()) };
+        if result < 0 {
+            return None;
+        }
+
+        let bytes = glib::Bytes::from(&self.buffer);
+        Some(gdk::MemoryTexture::new(
+            width,
+            height,
+            gdk::MemoryFormat::R8g8b8x8,
+            &bytes,
+            stride,
+        ))
+    }
+}
+
+impl Drop for SoftwareRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            mpv_render_context_free(self.context);
+            drop(Box::from_raw(self.sender));
+        }
+    }
+}
+
+unsafe extern "C" fn on_update(data: *mut c_void) {
+    let sender = unsafe { &*(data as *const UnboundedSender<()>) };
+    sender.send(()).ok();
+}
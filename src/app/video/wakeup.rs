@@ -0,0 +1,85 @@
+use std::ffi::c_void;
+
+use libmpv2::Mpv;
+use libmpv2_sys::mpv_set_wakeup_callback;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+/// Forwards mpv's wakeup callback, which is called from mpv's own threads,
+/// so that events are only handled when mpv has some
+pub struct Wakeup {
+    sender: *mut UnboundedSender<()>,
+}
+
+impl Wakeup {
+    pub fn new(mpv: &Mpv) -> (Self, UnboundedReceiver<()>) {
+        let (sender, receiver) = unbounded_channel();
+
+        let sender = Box::into_raw(Box::new(sender));
+        unsafe {
+            mpv_set_wakeup_callback(mpv.ctx.as_ptr(), Some(on_wakeup), sender as *mut c_void);
+        }
+
+        (Self { sender }, receiver)
+    }
+}
+
+impl Drop for Wakeup {
+    fn drop(&mut self) {
+        unsafe {
+            drop(Box::from_raw(self.sender));
+        }
+    }
+}
+
+unsafe extern "C" fn on_wakeup(data: *mut c_void) {
+    let sender = unsafe { &*(data as *const UnboundedSender<()>) };
+    sender.send(()).ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use libmpv2::{Format, Mpv};
+
+    use super::Wakeup;
+
+    const IDLE_INTERVAL: Duration = Duration::from_secs(1);
+
+    #[test]
+    #[ignore = "timing test that needs libmpv"]
+    fn idle_player_does_not_wake_up() {
+        let mut mpv = Mpv::with_initializer(|init| {
+            init.set_property("vo", "null")?;
+            init.set_property("ao", "null")?;
+            init.set_property("idle", "yes")?;
+            Ok(())
+        })
+        .expect("Failed to create mpv");
+
+        let (wakeup, mut receiver) = Wakeup::new(&mpv);
+
+        for name in ["pause", "time-pos", "duration"] {
+            mpv.observe_property(name, Format::String, 0)
+                .expect("Failed to observe property");
+        }
+
+        // Handle the events of mpv starting up
+        thread::sleep(Duration::from_millis(200));
+        while mpv.wait_event(0.0).is_some() {}
+        while receiver.try_recv().is_ok() {}
+
+        thread::sleep(IDLE_INTERVAL);
+
+        let mut wakeups = 0;
+        while receiver.try_recv().is_ok() {
+            wakeups += 1;
+        }
+
+        assert_eq!(wakeups, 0);
+
+        // mpv can call the wakeup callback until it is destroyed
+        drop(mpv);
+        drop(wakeup);
+    }
+}
@@ -0,0 +1,9 @@
+/// Origins allowed to navigate in the app and talk to the shell, besides the one loaded
+pub const TRUSTED_ORIGINS: &[&str] = &[
+    "http://127.0.0.1:11470",
+    "http://localhost:11470",
+    "https://web.stremio.com",
+];
+
+/// Schemes without an origin that can still be navigated to in the app
+pub const IN_APP_SCHEMES: &[&str] = &["about", "blob"];
@@ -1,10 +1,141 @@
+use std::cell::{Cell, RefCell};
+
 use adw::subclass::prelude::*;
+use gettextrs::gettext;
 use gtk::{gdk::RGBA, glib, prelude::*};
+use tracing::warn;
+use url::Url;
 use webkit::{WebView as WebKitWebView, prelude::*};
 
+use crate::app::webview::config::{IN_APP_SCHEMES, TRUSTED_ORIGINS};
+
 #[derive(Default)]
 pub struct WebView {
     pub webview: WebKitWebView,
+    pub trusted_origins: RefCell<Vec<String>>,
+    pub ipc_enabled: Cell<bool>,
+    ipc_registered: Cell<bool>,
+    pub error_page: adw::StatusPage,
+    copy_button: gtk::Button,
+    pub diagnostics: RefCell<String>,
+}
+
+impl WebView {
+    pub fn trust_origin(&self, uri: &str) {
+        if let Some(origin) = origin(uri) {
+            self.trusted_origins.borrow_mut().push(origin);
+        }
+    }
+
+    pub fn is_trusted(&self, uri: &str) -> bool {
+        origin(uri).is_some_and(|origin| self.trusted_origins.borrow().contains(&origin))
+    }
+
+    /// Whether the page currently loaded may use the ipc bridge
+    pub fn is_page_trusted(&self) -> bool {
+        self.webview.uri().is_some_and(|uri| self.is_trusted(&uri))
+    }
+
+    pub fn allows_navigation(&self, uri: &str) -> bool {
+        self.is_trusted(uri)
+            || Url::parse(uri).is_ok_and(|url| IN_APP_SCHEMES.contains(&url.scheme()))
+    }
+
+    /// Only exposes the ipc handler to trusted origins
+    pub fn update_ipc(&self) {
+        if !self.ipc_enabled.get() {
+            return;
+        }
+
+        let trusted = self.is_page_trusted();
+        if self.ipc_registered.get() == trusted {
+            return;
+        }
+
+        if let Some(user_content_manager) = self.webview.user_content_manager() {
+            match trusted {
+                true => {
+                    user_content_manager.register_script_message_handler("ipc", None);
+                }
+                false => {
+                    warn!("Detaching ipc from untrusted page");
+                    user_content_manager.unregister_script_message_handler("ipc", None);
+                }
+            }
+
+            self.ipc_registered.set(trusted);
+        }
+    }
+
+    pub fn show_error(&self, diagnostics: String) {
+        *self.diagnostics.borrow_mut() = diagnostics;
+
+        self.copy_button.set_label(&gettext("Copy Diagnostics"));
+        self.webview.set_visible(false);
+        self.error_page.set_visible(true);
+    }
+
+    pub fn reload(&self) {
+        self.error_page.set_visible(false);
+        self.webview.set_visible(true);
+
+        match self.webview.uri() {
+            Some(uri) => self.webview.load_uri(&uri),
+            None => self.webview.reload(),
+        }
+    }
+
+    fn setup_error_page(&self) {
+        self.copy_button.set_label(&gettext("Copy Diagnostics"));
+        self.copy_button.add_css_class("pill");
+        self.copy_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |button| {
+                button.clipboard().set_text(&imp.diagnostics.borrow());
+                button.set_label(&gettext("Copied"));
+            }
+        ));
+
+        let reload_button = gtk::Button::builder()
+            .label(gettext("Reload"))
+            .css_classes(["pill", "suggested-action"])
+            .build();
+        reload_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |_| {
+                imp.reload();
+            }
+        ));
+
+        let buttons = gtk::Box::builder()
+            .spacing(12)
+            .halign(gtk::Align::Center)
+            .build();
+        buttons.append(&self.copy_button);
+        buttons.append(&reload_button);
+
+        self.error_page.set_icon_name(Some("dialog-error-symbolic"));
+        self.error_page
+            .set_title(&gettext("Stremio stopped working"));
+        self.error_page.set_description(Some(&gettext(
+            "The interface crashed several times in a row",
+        )));
+        self.error_page.set_child(Some(&buttons));
+        self.error_page.set_vexpand(true);
+        self.error_page.set_hexpand(true);
+        self.error_page.set_visible(false);
+    }
+}
+
+fn origin(uri: &str) -> Option<String> {
+    let url = Url::parse(uri).ok()?;
+
+    match url.origin() {
+        origin if origin.is_tuple() => Some(origin.ascii_serialization()),
+        _ => None,
+    }
 }
 
 #[glib::object_subclass]
@@ -20,6 +151,11 @@ impl ObjectImpl for WebView {
 
         let object = self.obj();
 
+        *self.trusted_origins.borrow_mut() = TRUSTED_ORIGINS
+            .iter()
+            .map(|origin| origin.to_string())
+            .collect();
+
         self.webview.set_vexpand(true);
         self.webview.set_hexpand(true);
         self.webview
@@ -32,7 +168,18 @@ impl ObjectImpl for WebView {
             settings.set_enable_webaudio(false);
         }
 
+        self.webview.connect_uri_notify(glib::clone!(
+            #[weak(rename_to = imp)]
+            self,
+            move |_| {
+                imp.update_ipc();
+            }
+        ));
+
+        self.setup_error_page();
+
         object.append(&self.webview);
+        object.append(&self.error_page);
     }
 }
 
@@ -1,3 +1,4 @@
+mod config;
 mod imp;
 
 use std::rc::Rc;
@@ -7,10 +8,10 @@ use gtk::{
     gio::Cancellable,
     glib::{self, clone, object::Cast},
 };
-use tracing::error;
+use tracing::{error, warn};
 use webkit::{
-    NavigationPolicyDecision, PolicyDecisionType, UserContentInjectedFrames, UserScript,
-    UserScriptInjectionTime, prelude::WebViewExt,
+    Download, LoadEvent, NavigationPolicyDecision, PolicyDecisionType, UserContentInjectedFrames,
+    UserScript, UserScriptInjectionTime, WebProcessTerminationReason, prelude::*,
 };
 
 glib::wrapper! {
@@ -32,9 +33,24 @@ impl WebView {
     pub fn load_uri(&self, uri: &str) {
         let widget = self.imp();
 
+        widget.trust_origin(uri);
         widget.webview.load_uri(uri);
     }
 
+    pub fn uri(&self) -> Option<String> {
+        self.imp().webview.uri().map(|uri| uri.to_string())
+    }
+
+    /// Loads the last page again, which also brings back the route it was on
+    pub fn reload(&self) {
+        self.imp().reload();
+    }
+
+    /// Replaces the page with an error and a way to copy the given diagnostics
+    pub fn show_error(&self, diagnostics: String) {
+        self.imp().show_error(diagnostics);
+    }
+
     pub fn inject_script(&self, script: &'static str) {
         let widget = self.imp();
 
@@ -70,6 +86,10 @@ impl WebView {
     pub fn send(&self, message: &str) {
         let widget = self.imp();
 
+        if !widget.is_page_trusted() {
+            return;
+        }
+
         let serialized_message =
             serde_json::to_string(&message).expect("Failed to serialize as JSON string");
         let script = format!("__postMessage({serialized_message})");
@@ -88,19 +108,26 @@ impl WebView {
         let webview = self;
 
         if let Some(user_content_manager) = widget.webview.user_content_manager() {
-            user_content_manager.register_script_message_handler("ipc", None);
             user_content_manager.connect_script_message_received(
                 Some("ipc"),
                 clone!(
                     #[weak]
                     webview,
                     move |_, value| {
+                        if !webview.imp().is_page_trusted() {
+                            warn!("Ignoring ipc message from untrusted page");
+                            return;
+                        }
+
                         let message = value.to_string();
                         callback(webview, &message);
                     }
                 ),
             );
         }
+
+        widget.ipc_enabled.set(true);
+        widget.update_ipc();
     }
 
     pub fn connect_fullscreen<T: Fn(bool) + 'static>(&self, callback: T) {
@@ -121,22 +148,82 @@ impl WebView {
         });
     }
 
-    pub fn connect_open_external<T: Fn(&str) + 'static>(&self, callback: T) {
+    pub fn connect_crashed<T: Fn(&str) + 'static>(&self, callback: T) {
         let widget = self.imp();
 
         widget
             .webview
-            .connect_decide_policy(move |_, decision, decision_type| {
-                if let PolicyDecisionType::NewWindowAction = decision_type
-                    && let Some(decision) = decision.downcast_ref::<NavigationPolicyDecision>()
-                    && let Some(mut action) = decision.navigation_action()
-                    && let Some(request) = action.request()
-                    && let Some(uri) = request.uri()
-                {
-                    callback(&uri);
+            .connect_web_process_terminated(move |_, reason| {
+                let reason = match reason {
+                    WebProcessTerminationReason::Crashed => "crashed",
+                    WebProcessTerminationReason::ExceededMemoryLimit => "exceeded the memory limit",
+                    // Terminated on purpose by the shell
+                    WebProcessTerminationReason::TerminatedByApi => return,
+                    _ => "terminated",
+                };
+
+                callback(reason);
+            });
+    }
+
+    pub fn connect_download_started<T: Fn(&Download) + 'static>(&self, callback: T) {
+        let widget = self.imp();
+
+        if let Some(network_session) = widget.webview.network_session() {
+            network_session.connect_download_started(move |_, download| {
+                callback(download);
+            });
+        }
+    }
+
+    /// New windows and navigations away from trusted origins are opened externally
+    pub fn connect_open_external<T: Fn(&str) + 'static>(&self, callback: T) {
+        let widget = self.imp();
+        let webview = self;
+
+        let callback = Rc::new(callback);
+
+        widget.webview.connect_decide_policy(clone!(
+            #[strong]
+            callback,
+            move |_, decision, decision_type| {
+                if decision_type != PolicyDecisionType::NewWindowAction {
+                    return false;
                 }
 
+                let Some(uri) = decision
+                    .downcast_ref::<NavigationPolicyDecision>()
+                    .and_then(|decision| decision.navigation_action())
+                    .and_then(|mut action| action.request())
+                    .and_then(|request| request.uri())
+                else {
+                    return false;
+                };
+
+                decision.ignore();
+                callback(&uri);
+
                 true
-            });
+            }
+        ));
+
+        // Navigation decisions do not tell the main frame from subframes such as embedded
+        // players, so main frame loads are checked once they start instead
+        widget.webview.connect_load_changed(clone!(
+            #[weak]
+            webview,
+            move |view, event| {
+                if !matches!(event, LoadEvent::Started | LoadEvent::Redirected) {
+                    return;
+                }
+
+                if let Some(uri) = view.uri()
+                    && !webview.imp().allows_navigation(&uri)
+                {
+                    view.stop_loading();
+                    callback(&uri);
+                }
+            }
+        ));
     }
 }
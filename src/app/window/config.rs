@@ -0,0 +1,8 @@
+/// Portion of the monitor a window fitted to the video can take up
+pub const MONITOR_FIT_RATIO: f64 = 0.9;
+
+/// Opened externally without asking, any other scheme needs a confirmation
+pub const EXTERNAL_SCHEMES: &[&str] = &["http", "https", "mailto", "magnet"];
+/// Played without asking, any other scheme needs a confirmation
+pub const MEDIA_SCHEMES: &[&str] = &["stremio", "http", "https", "magnet"];
+pub const BLOCKED_SCHEMES: &[&str] = &["file", "data", "javascript", "blob", "about"];
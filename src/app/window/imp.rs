@@ -1,6 +1,6 @@
-use std::sync::Arc;
+use std::{cell::Cell, sync::Arc};
 
-use adw::subclass::prelude::*;
+use adw::{prelude::*, subclass::prelude::*};
 use ashpd::{
     WindowIdentifier,
     desktop::{
@@ -11,15 +11,26 @@ use ashpd::{
     },
     enumflags2::BitFlags,
 };
+use gettextrs::gettext;
 use gtk::{
+    gdk, gio,
     glib::{self, clone, subclass::InitializingObject},
-    prelude::WidgetExt,
+    prelude::*,
 };
 use tokio::sync::Mutex;
-use tracing::error;
+use tracing::{error, warn};
 use url::Url;
 
-use crate::spawn_local;
+use crate::{
+    app::{
+        Application,
+        config::AUTOSTART_COMMAND,
+        presentation,
+        settings::CloseAction,
+        window::config::{BLOCKED_SCHEMES, EXTERNAL_SCHEMES, MEDIA_SCHEMES, MONITOR_FIT_RATIO},
+    },
+    spawn_local,
+};
 
 #[derive(Default, gtk::CompositeTemplate)]
 #[template(file = "window.ui")]
@@ -27,12 +38,24 @@ pub struct Window {
     #[template_child]
     pub header: TemplateChild<adw::HeaderBar>,
     #[template_child]
+    pub audio_device_button: TemplateChild<gtk::MenuButton>,
+    #[template_child]
+    pub shader_presets_section: TemplateChild<gio::Menu>,
+    #[template_child]
+    pub monitors_section: TemplateChild<gio::Menu>,
+    #[template_child]
+    pub fullscreen_monitors_section: TemplateChild<gio::Menu>,
+    #[template_child]
     pub overlay: TemplateChild<gtk::Overlay>,
     pub inhibit_request: Arc<Mutex<Option<Request<()>>>>,
+    inhibit_flags: Cell<BitFlags<InhibitFlags>>,
+    inhibit_cookie: Cell<Option<u32>>,
+    pub aspect_ratio: Cell<Option<f64>>,
+    adjusting_size: Cell<bool>,
 }
 
 impl Window {
-    pub fn request_backgound(&self) {
+    pub fn request_backgound(&self, autostart: bool) {
         let object = self.obj();
 
         spawn_local!(clone!(
@@ -40,18 +63,39 @@ impl Window {
             object,
             async move {
                 if let Some(identifier) = WindowIdentifier::from_native(&object).await {
-                    let request = Background::request().identifier(identifier);
-                    request
-                        .send()
-                        .await
-                        .map_err(|e| error!("Failed to set background mode: {e}"))
-                        .ok();
+                    let reason = gettext("Keep the streaming server running");
+                    let request = Background::request()
+                        .identifier(identifier)
+                        .reason(reason.as_str())
+                        .auto_start(autostart)
+                        .command(AUTOSTART_COMMAND)
+                        .dbus_activatable(false);
+
+                    match request.send().await.and_then(|request| request.response()) {
+                        Ok(response) if autostart && !response.auto_start() => {
+                            warn!("Autostart was not allowed");
+                        }
+                        Ok(_) => {}
+                        Err(e) => error!("Failed to set background mode: {e}"),
+                    }
                 }
             }
         ));
     }
 
-    pub fn disable_idling(&self) {
+    pub fn set_inhibit(&self, idle: bool, suspend: bool) {
+        let mut flags = BitFlags::empty();
+        if idle {
+            flags.insert(InhibitFlags::Idle);
+        }
+        if suspend {
+            flags.insert(InhibitFlags::Suspend);
+        }
+
+        if self.inhibit_flags.replace(flags) == flags {
+            return;
+        }
+
         let object = self.obj();
         let inhibit_request = self.inhibit_request.clone();
 
@@ -59,50 +103,211 @@ impl Window {
             #[weak]
             object,
             async move {
-                if let Some(identifier) = WindowIdentifier::from_native(&object).await
-                    && let Ok(proxy) = InhibitProxy::new().await
-                {
-                    let mut flags = BitFlags::empty();
-                    flags.insert(InhibitFlags::Idle);
-
-                    let reason = "Prevent screen from going blank during media playback";
+                let mut inhibit_request = inhibit_request.lock().await;
 
-                    let mut inhibit_request = inhibit_request.lock().await;
-                    *inhibit_request = proxy
-                        .inhibit(Some(&identifier), flags, reason)
+                if let Some(request) = inhibit_request.take() {
+                    request
+                        .close()
                         .await
-                        .map_err(|e| error!("Failed to prevent idling: {e}"))
+                        .map_err(|e| error!("Failed to release inhibitor: {e}"))
                         .ok();
                 }
+
+                let window = object.imp();
+                window.uninhibit_fallback();
+
+                if flags.is_empty() {
+                    return;
+                }
+
+                let reason = match flags.contains(InhibitFlags::Idle) {
+                    true => gettext("Prevent screen from going blank during media playback"),
+                    false => gettext("Keep the system awake while streaming"),
+                };
+
+                let identifier = WindowIdentifier::from_native(&object).await;
+                let request = match InhibitProxy::new().await {
+                    Ok(proxy) => proxy.inhibit(identifier.as_ref(), flags, &reason).await,
+                    Err(e) => Err(e),
+                };
+
+                match request {
+                    Ok(request) => *inhibit_request = Some(request),
+                    Err(e) => {
+                        warn!("Failed to inhibit through portal, falling back: {e}");
+                        window.inhibit_fallback(flags, &reason);
+                    }
+                }
             }
         ));
     }
 
-    pub fn enable_idling(&self) {
-        let inhibit_request = self.inhibit_request.clone();
+    fn inhibit_fallback(&self, flags: BitFlags<InhibitFlags>, reason: &str) {
+        let object = self.obj();
 
-        spawn_local!(async move {
-            let mut inhibit_request = inhibit_request.lock().await;
-            if let Some(request) = inhibit_request.take() {
-                request
-                    .close()
-                    .await
-                    .map_err(|e| error!("Failed to allow idling: {e}"))
-                    .ok();
+        if let Some(app) = object.application() {
+            let mut app_flags = gtk::ApplicationInhibitFlags::empty();
+            if flags.contains(InhibitFlags::Idle) {
+                app_flags.insert(gtk::ApplicationInhibitFlags::IDLE);
             }
-        });
+            if flags.contains(InhibitFlags::Suspend) {
+                app_flags.insert(gtk::ApplicationInhibitFlags::SUSPEND);
+            }
+
+            match app.inhibit(Some(&*object), app_flags, Some(reason)) {
+                0 => error!("Failed to inhibit through application"),
+                cookie => self.inhibit_cookie.set(Some(cookie)),
+            }
+        }
+    }
+
+    fn uninhibit_fallback(&self) {
+        if let Some(cookie) = self.inhibit_cookie.take()
+            && let Some(app) = self.obj().application()
+        {
+            app.uninhibit(cookie);
+        }
+    }
+
+    pub fn fit_video(&self, width: i32, height: i32, scale: f64) {
+        let object = self.obj();
+
+        if object.is_fullscreen() {
+            return;
+        }
+
+        if object.is_maximized() {
+            object.unmaximize();
+        }
+
+        let header = self.header_height();
+        let mut width = f64::from(width) * scale;
+        let mut height = f64::from(height) * scale;
+
+        if let Some(bounds) = object
+            .surface()
+            .and_then(|surface| object.display().monitor_at_surface(&surface))
+            .map(|monitor| monitor.geometry())
+        {
+            let max_width = f64::from(bounds.width()) * MONITOR_FIT_RATIO;
+            let max_height = f64::from(bounds.height()) * MONITOR_FIT_RATIO - f64::from(header);
+            let factor = (max_width / width).min(max_height / height).min(1.0);
+
+            width *= factor;
+            height *= factor;
+        }
+
+        self.adjusting_size.set(true);
+        object.set_default_size(width.round() as i32, height.round() as i32 + header);
+        self.adjusting_size.set(false);
+    }
+
+    /// Follows a resize along one side with the other one while an aspect ratio is locked
+    fn keep_aspect_ratio(&self, resized: gtk::Orientation) {
+        let object = self.obj();
+
+        let Some(ratio) = self.aspect_ratio.get() else {
+            return;
+        };
+
+        if self.adjusting_size.get() || object.is_fullscreen() || object.is_maximized() {
+            return;
+        }
+
+        let header = self.header_height();
+        let (width, height) = object.default_size();
+
+        let size = match resized {
+            gtk::Orientation::Horizontal => {
+                (width, (f64::from(width) / ratio).round() as i32 + header)
+            }
+            _ => ((f64::from(height - header) * ratio).round() as i32, height),
+        };
+
+        if size != (width, height) {
+            self.adjusting_size.set(true);
+            object.set_default_size(size.0, size.1);
+            self.adjusting_size.set(false);
+        }
+    }
+
+    /// Preferred fullscreen monitor, none when unset or unplugged to let the compositor pick one
+    pub fn preferred_monitor(&self) -> Option<gdk::Monitor> {
+        let app = self.obj().application().and_downcast::<Application>()?;
+        let preference = app.imp().settings.borrow().fullscreen_monitor.clone()?;
+
+        let monitor = presentation::find_monitor(&preference);
+        if monitor.is_none() {
+            warn!("Preferred fullscreen monitor is not connected");
+        }
+
+        monitor
     }
 
+    fn header_height(&self) -> i32 {
+        match self.header.is_visible() {
+            true => self.header.height(),
+            false => 0,
+        }
+    }
+
+    /// Opens well known schemes right away, asks before handing others to the system
     pub fn open_uri(&self, uri: String) {
+        let Some(url) = checked_uri(&uri) else {
+            return;
+        };
+
+        match EXTERNAL_SCHEMES.contains(&url.scheme()) {
+            true => self.launch_uri(url),
+            false => self.confirm_uri(url, |window, url| window.launch_uri(url)),
+        }
+    }
+
+    /// Plays well known schemes right away, asks before playing others
+    pub fn open_media_uri<F: Fn(String) + 'static>(&self, uri: String, open: F) {
+        let Some(url) = checked_uri(&uri) else {
+            return;
+        };
+
+        match MEDIA_SCHEMES.contains(&url.scheme()) {
+            true => open(uri),
+            false => self.confirm_uri(url, move |_, _| open(uri.clone())),
+        }
+    }
+
+    fn confirm_uri<F: Fn(&Self, Url) + 'static>(&self, url: Url, open: F) {
+        let dialog = adw::AlertDialog::builder()
+            .heading(gettext("Open External Link?"))
+            .body(url.as_str())
+            .close_response("cancel")
+            .default_response("cancel")
+            .build();
+
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("open", &gettext("_Open"))]);
+        dialog.set_response_appearance("open", adw::ResponseAppearance::Suggested);
+
+        dialog.connect_response(
+            Some("open"),
+            clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_, _| {
+                    open(&window, url.clone());
+                }
+            ),
+        );
+
+        dialog.present(Some(&*self.obj()));
+    }
+
+    fn launch_uri(&self, uri: Url) {
         let object = self.obj();
 
         spawn_local!(clone!(
             #[weak]
             object,
             async move {
-                if let Ok(uri) = Url::parse(&uri)
-                    && let Some(identifier) = WindowIdentifier::from_native(&object).await
-                {
+                if let Some(identifier) = WindowIdentifier::from_native(&object).await {
                     let request = OpenFileRequest::default().identifier(identifier);
 
                     request
@@ -135,9 +340,19 @@ impl ObjectImpl for Window {
     fn constructed(&self) {
         self.parent_constructed();
 
+        let object = self.obj();
+
         if cfg!(debug_assertions) {
-            self.obj().add_css_class("devel");
+            object.add_css_class("devel");
         }
+
+        object.connect_default_width_notify(|window| {
+            window.imp().keep_aspect_ratio(gtk::Orientation::Horizontal);
+        });
+
+        object.connect_default_height_notify(|window| {
+            window.imp().keep_aspect_ratio(gtk::Orientation::Vertical);
+        });
     }
 }
 
@@ -148,14 +363,35 @@ impl WindowImpl for Window {
         self.parent_activate_default();
 
         let widget = self.obj();
-        widget.request_backgound();
+
+        let autostart = widget
+            .application()
+            .and_downcast::<Application>()
+            .is_some_and(|app| app.imp().settings.borrow().autostart);
+
+        widget.request_backgound(autostart);
     }
 
     fn close_request(&self) -> glib::Propagation {
         self.parent_close_request();
 
         let widget = self.obj();
-        widget.set_visible(false);
+
+        let close_action = widget
+            .application()
+            .and_downcast::<Application>()
+            .map(|app| app.close_action())
+            .unwrap_or(CloseAction::Quit);
+
+        match close_action {
+            CloseAction::Tray => widget.set_visible(false),
+            CloseAction::Minimize => widget.minimize(),
+            CloseAction::Quit => {
+                if let Some(app) = widget.application() {
+                    app.quit();
+                }
+            }
+        }
 
         glib::Propagation::Stop
     }
@@ -163,3 +399,17 @@ impl WindowImpl for Window {
 
 impl ApplicationWindowImpl for Window {}
 impl AdwApplicationWindowImpl for Window {}
+
+fn checked_uri(uri: &str) -> Option<Url> {
+    let Ok(url) = Url::parse(uri) else {
+        warn!("Refusing to open invalid uri {uri}");
+        return None;
+    };
+
+    if BLOCKED_SCHEMES.contains(&url.scheme()) {
+        warn!("Refusing to open {} uri", url.scheme());
+        return None;
+    }
+
+    Some(url)
+}
@@ -1,13 +1,16 @@
+mod config;
 mod imp;
 
+use std::rc::Rc;
+
 use adw::subclass::prelude::*;
 use gtk::{
-    Widget, gio,
-    glib::{self, object::IsA},
+    Widget, gdk, gio,
+    glib::{self, clone, object::IsA},
     prelude::*,
 };
 
-use crate::app::Application;
+use crate::app::{Application, presentation, shaders::ShaderPreset, video::AudioDevice};
 
 glib::wrapper! {
     pub struct Window(ObjectSubclass<imp::Window>)
@@ -28,17 +31,98 @@ impl Window {
         window.overlay.set_child(Some(widget));
     }
 
+    pub fn take_underlay(&self) -> Option<Widget> {
+        let window = self.imp();
+
+        let widget = window.overlay.child();
+        window.overlay.set_child(None::<&Widget>);
+        widget
+    }
+
     pub fn set_overlay(&self, widget: &impl IsA<Widget>) {
         let window = self.imp();
 
         window.overlay.add_overlay(&graphics_offload(widget));
     }
 
+    pub fn add_overlay(&self, widget: &impl IsA<Widget>) {
+        let window = self.imp();
+
+        window.overlay.add_overlay(widget);
+    }
+
     pub fn set_fullscreen(&self, fullscreen: bool) {
         let window = self.imp();
 
         window.header.set_visible(!fullscreen);
-        self.set_fullscreened(fullscreen);
+
+        match fullscreen.then(|| window.preferred_monitor()).flatten() {
+            Some(monitor) => self.fullscreen_on_monitor(&monitor),
+            None => self.set_fullscreened(fullscreen),
+        }
+    }
+
+    /// Resizes the window so the video fills it at the given scale, within the monitor bounds
+    pub fn fit_video(&self, width: i32, height: i32, scale: f64) {
+        self.imp().fit_video(width, height, scale);
+    }
+
+    /// Keeps the video area at the given aspect ratio while the window is resized
+    pub fn set_aspect_ratio(&self, ratio: Option<f64>) {
+        self.imp().aspect_ratio.set(ratio);
+    }
+
+    pub fn set_audio_devices(&self, devices: &[AudioDevice]) {
+        let window = self.imp();
+
+        let menu = gio::Menu::new();
+        for device in devices {
+            let item = gio::MenuItem::new(Some(&device.description), None);
+            item.set_action_and_target_value(
+                Some("app.audio-device"),
+                Some(&device.name.to_variant()),
+            );
+            menu.append_item(&item);
+        }
+
+        window.audio_device_button.set_menu_model(Some(&menu));
+        window.audio_device_button.set_visible(devices.len() > 1);
+    }
+
+    pub fn set_shader_presets(&self, presets: &[ShaderPreset]) {
+        let window = self.imp();
+
+        window.shader_presets_section.remove_all();
+        for preset in presets {
+            let item = gio::MenuItem::new(Some(&preset.name), None);
+            item.set_action_and_target_value(
+                Some("app.shader-preset"),
+                Some(&preset.name.to_variant()),
+            );
+            window.shader_presets_section.append_item(&item);
+        }
+    }
+
+    pub fn set_monitors(&self, monitors: &[gdk::Monitor]) {
+        let window = self.imp();
+
+        for (section, action) in [
+            (&*window.monitors_section, "app.present-on"),
+            (
+                &*window.fullscreen_monitors_section,
+                "app.fullscreen-monitor",
+            ),
+        ] {
+            section.remove_all();
+            for monitor in monitors {
+                if let Some(id) = presentation::monitor_id(monitor) {
+                    let name = presentation::monitor_name(monitor);
+                    let item = gio::MenuItem::new(Some(&name), None);
+                    item.set_action_and_target_value(Some(action), Some(&id.to_variant()));
+                    section.append_item(&item);
+                }
+            }
+        }
     }
 
     pub fn connect_visibility<T: Fn(bool) + 'static>(&self, callback: T) {
@@ -47,21 +131,42 @@ impl Window {
         });
     }
 
-    fn request_backgound(&self) {
-        self.imp().request_backgound();
+    /// Hidden to the tray, minimized or otherwise not visible on screen
+    pub fn connect_hidden<T: Fn(bool) + 'static>(&self, callback: T) {
+        let callback = Rc::new(callback);
+
+        self.connect_visible_notify(clone!(
+            #[strong]
+            callback,
+            move |window| {
+                callback(window.is_hidden());
+            }
+        ));
+
+        self.connect_suspended_notify(move |window| {
+            callback(window.is_hidden());
+        });
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        !self.is_visible() || self.is_suspended()
     }
 
-    pub fn disable_idling(&self) {
-        self.imp().disable_idling();
+    pub fn request_backgound(&self, autostart: bool) {
+        self.imp().request_backgound(autostart);
     }
 
-    pub fn enable_idling(&self) {
-        self.imp().enable_idling();
+    pub fn set_inhibit(&self, idle: bool, suspend: bool) {
+        self.imp().set_inhibit(idle, suspend);
     }
 
     pub fn open_uri(&self, uri: String) {
         self.imp().open_uri(uri);
     }
+
+    pub fn open_media_uri<F: Fn(String) + 'static>(&self, uri: String, open: F) {
+        self.imp().open_media_uri(uri, open);
+    }
 }
 
 fn graphics_offload(widget: &impl IsA<Widget>) -> gtk::GraphicsOffload {
@@ -0,0 +1,40 @@
+use clap::Subcommand;
+use gtk::glib::{Variant, variant::ToVariant};
+
+#[derive(Subcommand, Debug)]
+pub enum CtlCommand {
+    /// Toggle between play and pause
+    PlayPause,
+    /// Stop playback
+    Stop,
+    /// Seek by a relative amount of seconds
+    Seek {
+        #[arg(allow_negative_numbers = true)]
+        offset: f64,
+    },
+    /// Set the volume, from 0 to 100
+    SetVolume { volume: f64 },
+    /// Toggle fullscreen
+    ToggleFullscreen,
+    /// Open a stremio:// link or a media url
+    OpenUri { uri: String },
+    /// Show the window
+    Show,
+    /// Hide the window
+    Hide,
+}
+
+impl CtlCommand {
+    pub fn action(&self) -> (&'static str, Option<Variant>) {
+        match self {
+            CtlCommand::PlayPause => ("play-pause", None),
+            CtlCommand::Stop => ("stop", None),
+            CtlCommand::Seek { offset } => ("seek", Some(offset.to_variant())),
+            CtlCommand::SetVolume { volume } => ("set-volume", Some(volume.to_variant())),
+            CtlCommand::ToggleFullscreen => ("toggle-fullscreen", None),
+            CtlCommand::OpenUri { uri } => ("open-uri", Some(uri.to_variant())),
+            CtlCommand::Show => ("show", None),
+            CtlCommand::Hide => ("hide", None),
+        }
+    }
+}
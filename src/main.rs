@@ -1,17 +1,19 @@
 mod app;
 mod config;
+mod ctl;
 mod server;
 mod utils;
 
 use std::{env, ptr};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use gettextrs::LocaleCategory;
 use gtk::glib::{ExitCode, object::ObjectExt};
 
 use crate::{
     app::Application,
     config::{GETTEXT_DIR_DEV, GETTEXT_DIR_FLATPAK, GETTEXT_DOMAIN},
+    ctl::CtlCommand,
     server::Server,
 };
 
@@ -21,6 +23,23 @@ struct Args {
     /// Open dev tools
     #[arg(short, long)]
     dev: bool,
+    /// Start minimized to the tray
+    #[arg(long)]
+    hidden: bool,
+    /// Render video on the CPU instead of OpenGL
+    #[arg(long)]
+    software_rendering: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Control a running instance
+    Ctl {
+        #[command(subcommand)]
+        command: CtlCommand,
+    },
 }
 
 #[tokio::main]
@@ -38,6 +57,21 @@ async fn main() -> ExitCode {
         .expect("Failed to set the text domain encoding");
     gettextrs::textdomain(GETTEXT_DOMAIN).expect("Failed to switch text domain");
 
+    let args = Args::parse();
+
+    if let Some(Command::Ctl { command }) = args.command {
+        let (name, parameter) = command.action();
+
+        let app = Application::new();
+        return match app.activate_remote_action(name, parameter.as_ref()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
     let library = unsafe { libloading::os::unix::Library::new("libepoxy.so.0") }
         .expect("Failed to load libepoxy");
 
@@ -47,13 +81,18 @@ async fn main() -> ExitCode {
             .unwrap_or(ptr::null())
     });
 
-    let args = Args::parse();
-
     let mut server = Server::new();
     server.setup().await.expect("Failed to setup server");
     server.start(args.dev).expect("Failed to start server");
 
     let app = Application::new();
     app.set_property("dev_mode", args.dev);
+    app.set_property("hidden", args.hidden);
+    app.set_property("software-rendering", args.software_rendering);
+
+    if let Some(events) = server.events() {
+        app.watch_server(events);
+    }
+
     app.run()
 }
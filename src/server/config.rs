@@ -2,3 +2,4 @@ pub const UPDATER_ENDPOINT: &str = "https://www.strem.io/updater/server/check";
 pub const DOWNLOAD_ENDPOINT: &str = "https://dl.strem.io/server/vVERSION/desktop/server.js";
 pub const FILE: &str = "server.js";
 pub const VERSION_FILE: &str = "server_version";
+pub const STATS_ENDPOINT: &str = "http://127.0.0.1:11470/stats.json";
@@ -10,12 +10,14 @@ use std::{
 
 use anyhow::{Context, Ok};
 use serde::Deserialize;
+use serde_json::{Map, Value};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 use tracing::debug;
 use url::Url;
 
 use crate::{
     config::DATA_DIR,
-    server::config::{DOWNLOAD_ENDPOINT, FILE, UPDATER_ENDPOINT, VERSION_FILE},
+    server::config::{DOWNLOAD_ENDPOINT, FILE, STATS_ENDPOINT, UPDATER_ENDPOINT, VERSION_FILE},
 };
 
 #[derive(Deserialize, Debug)]
@@ -24,10 +26,18 @@ struct ServerUpdaterResponse {
     latest_version: String,
 }
 
+#[derive(Debug)]
+pub enum ServerEvent {
+    Updated(String),
+    Stopped,
+}
+
 pub struct Server {
     process: Option<Child>,
     file: PathBuf,
     version_file: PathBuf,
+    sender: UnboundedSender<ServerEvent>,
+    receiver: Option<UnboundedReceiver<ServerEvent>>,
 }
 
 impl Server {
@@ -41,13 +51,21 @@ impl Server {
         let file = data_dir.join(FILE);
         let version_file = data_dir.join(VERSION_FILE);
 
+        let (sender, receiver) = unbounded_channel();
+
         Self {
             process: None,
             file,
             version_file,
+            sender,
+            receiver: Some(receiver),
         }
     }
 
+    pub fn events(&mut self) -> Option<UnboundedReceiver<ServerEvent>> {
+        self.receiver.take()
+    }
+
     pub async fn setup(&self) -> anyhow::Result<()> {
         let response = reqwest::get(UPDATER_ENDPOINT)
             .await
@@ -85,6 +103,10 @@ impl Server {
                     self.version_file.display()
                 )
             })?;
+
+            if current_version.is_some() {
+                self.sender.send(ServerEvent::Updated(latest_version)).ok();
+            }
         }
 
         Ok(())
@@ -102,10 +124,13 @@ impl Server {
             let reader = BufReader::new(stdout);
             let mut lines = reader.lines();
 
+            let sender = self.sender.clone();
             thread::spawn(move || {
                 while let Some(Result::Ok(line)) = lines.next() {
                     debug!(target: "server", "{}", line);
                 }
+
+                sender.send(ServerEvent::Stopped).ok();
             });
         }
 
@@ -123,6 +148,25 @@ impl Server {
     }
 }
 
+/// Whether any torrent engine of the server is currently downloading
+pub async fn is_active() -> anyhow::Result<bool> {
+    let engines = reqwest::get(STATS_ENDPOINT)
+        .await
+        .context("Failed to send request to stats endpoint")?
+        .json::<Map<String, Value>>()
+        .await
+        .context("Failed to parse stats response JSON")?;
+
+    let active = engines.values().any(|engine| {
+        engine
+            .get("downloadSpeed")
+            .and_then(Value::as_f64)
+            .is_some_and(|speed| speed > 0.0)
+    });
+
+    Ok(active)
+}
+
 impl Drop for Server {
     fn drop(&mut self) {
         self.stop().expect("Failed to stop server");